@@ -0,0 +1,28 @@
+// Statistically sound timings for every day, with HTML reports under target/criterion.
+// `perf` is still the quick way to eyeball everything at once; this is for comparing changes.
+use criterion::{criterion_group, criterion_main, Criterion};
+use solutions::days;
+use std::hint::black_box;
+
+fn bench_days(c: &mut Criterion) {
+    // Benches run from the crate's directory, but the inputs live at the repo root
+    std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+        .expect("repo root should exist");
+    for m in days::all() {
+        let stages = match m.stages() {
+            Ok(stages) => stages,
+            Err(e) => {
+                eprintln!("Skipping day {:02}: {e:#}", m.day());
+                continue;
+            }
+        };
+        let mut group = c.benchmark_group(format!("day{:02}", m.day()));
+        group.bench_function("parse", |b| b.iter(|| black_box((stages.parse)())));
+        group.bench_function("part one", |b| b.iter(|| black_box((stages.part_one)())));
+        group.bench_function("part two", |b| b.iter(|| black_box((stages.part_two)())));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_days);
+criterion_main!(benches);
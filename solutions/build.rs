@@ -0,0 +1,30 @@
+use std::{env, fs, path::Path};
+
+// With the embed-inputs feature, bake every inputs/dayNN.txt into the binary so it can run from anywhere.
+// Days without an input file yet just fall back to reading from disk at runtime.
+fn main() {
+    if env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_none() {
+        return;
+    }
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let inputs = Path::new(&manifest_dir).join("../inputs");
+    println!("cargo:rerun-if-changed={}", inputs.display());
+
+    let mut arms = String::new();
+    for day in 1..=25u8 {
+        let file = inputs.join(format!("day{day:02}.txt"));
+        if file.exists() {
+            let file = file.canonicalize().unwrap();
+            println!("cargo:rerun-if-changed={}", file.display());
+            arms.push_str(&format!(
+                "        {day} => Some(include_str!({:?})),\n",
+                file
+            ));
+        }
+    }
+    let code = format!(
+        "pub fn input(day: u8) -> Option<&'static str> {{\n    match day {{\n{arms}        _ => None,\n    }}\n}}\n"
+    );
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded_inputs.rs");
+    fs::write(out, code).unwrap();
+}
@@ -0,0 +1,3 @@
+#![no_main]
+
+solutions_fuzz::load_target!(11);
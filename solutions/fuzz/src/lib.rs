@@ -0,0 +1,21 @@
+// What every fuzz target does, so each one is just the day it's for
+use solutions::{days, input::AdventInput};
+
+pub use libfuzzer_sys;
+
+// Parsing (and validating) whatever libFuzzer throws at it should fail politely, never panic
+pub fn load(day: u8, data: &str) {
+    if let Ok(solution) = days::get(day) {
+        let _ = solution.check_input(AdventInput::from_text(data));
+    }
+}
+
+/// A fuzz target for one day's parser, e.g. `load_target!(5);`
+#[macro_export]
+macro_rules! load_target {
+    ($day:expr) => {
+        $crate::libfuzzer_sys::fuzz_target!(|data: &str| {
+            $crate::load($day, data);
+        });
+    };
+}
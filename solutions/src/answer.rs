@@ -0,0 +1,93 @@
+use crate::common::ocr;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Whatever a part of a puzzle spits out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum Answer {
+    Int(i64),
+    BigInt(i128),
+    Text(String),
+    // Letters drawn with '#' and '.', one row per line
+    Art(String),
+}
+impl Answer {
+    // Prefer the letters, but hang on to the art if we can't read it
+    pub fn from_art(art: impl Into<String>) -> Self {
+        let art = art.into();
+        match ocr::decode(&art) {
+            Ok(text) => Answer::Text(text),
+            Err(_) => Answer::Art(art),
+        }
+    }
+}
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(v) => write!(f, "{v}"),
+            Answer::BigInt(v) => write!(f, "{v}"),
+            Answer::Text(v) => write!(f, "{v}"),
+            // Start on a fresh line so the art isn't knocked out of alignment by a prefix
+            Answer::Art(v) => write!(f, "\n{v}"),
+        }
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(value: $t) -> Self {
+                match i64::try_from(value) {
+                    Ok(v) => Answer::Int(v),
+                    Err(_) => Answer::BigInt(value as i128),
+                }
+            }
+        })*
+    };
+}
+impl_from_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_pick_smallest_integer() {
+        assert_eq!(Answer::Int(42), 42usize.into());
+        assert_eq!(Answer::Int(-42), (-42isize).into());
+        assert_eq!(Answer::BigInt(u64::MAX as i128), u64::MAX.into());
+    }
+
+    #[test]
+    fn should_read_art() {
+        let art = "#...\n#...\n#...\n#...\n#...\n####";
+        assert_eq!(Answer::from("L"), Answer::from_art(art));
+        assert_eq!(Answer::Art("#".to_owned()), Answer::from_art("#"));
+    }
+
+    #[test]
+    fn should_round_trip_json() -> anyhow::Result<()> {
+        for answer in [
+            Answer::Int(7),
+            Answer::BigInt(i128::MAX),
+            Answer::from("abc"),
+            Answer::Art("#.#\n.#.".to_owned()),
+        ] {
+            let json = serde_json::to_string(&answer)?;
+            assert_eq!(answer, serde_json::from_str(&json)?);
+        }
+        Ok(())
+    }
+}
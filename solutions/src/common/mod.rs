@@ -0,0 +1,157 @@
+#![allow(dead_code)]
+pub mod ocr;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use std::{cmp::Reverse, collections::BinaryHeap, fmt::Debug};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+impl Point {
+    pub fn new(x: usize, y: usize) -> Self {
+        Point { x, y }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IPoint {
+    pub x: isize,
+    pub y: isize,
+}
+impl IPoint {
+    pub fn new(x: isize, y: isize) -> Self {
+        IPoint { x, y }
+    }
+}
+
+// Still vaguely wondering why these algos aren't in the standard lib tbh
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+pub fn lcm(input: &[usize]) -> usize {
+    if input.is_empty() {
+        return 0;
+    }
+    if input.len() == 1 {
+        return input[0];
+    }
+    let a = input[0];
+    let b = lcm(&input[1..]);
+    a * b / gcd(a, b)
+}
+
+// Builds a grid of text a cell at a time, e.g. for generating inputs
+pub fn draw_grid(width: usize, height: usize, mut cell: impl FnMut(Point) -> char) -> String {
+    let mut grid = String::with_capacity((width + 1) * height);
+    for y in 0..height {
+        if y > 0 {
+            grid.push('\n');
+        }
+        grid.extend((0..width).map(|x| cell(Point::new(x, y))));
+    }
+    grid
+}
+
+/// Orders `nodes` so that `a` comes before `b` whenever `before(a, b)`, otherwise keeping them as they were.
+/// Asking about every pair is quadratic, so use [toposort_edges] when the edges are easy to list.
+pub fn toposort<T: Clone + Debug>(nodes: &[T], before: impl Fn(&T, &T) -> bool) -> Result<Vec<T>> {
+    let n = nodes.len();
+    let edges = (0..n)
+        .cartesian_product(0..n)
+        .filter(|&(i, j)| i != j && before(&nodes[i], &nodes[j]));
+    toposort_edges(nodes, edges)
+}
+
+/// Orders `nodes` so that `nodes[i]` comes before `nodes[j]` for every edge `(i, j)`, otherwise keeping them as they were.
+/// Fails if the ordering goes round in a circle, saying where.
+pub fn toposort_edges<T: Clone + Debug>(
+    nodes: &[T],
+    edges: impl IntoIterator<Item = (usize, usize)>,
+) -> Result<Vec<T>> {
+    let n = nodes.len();
+    let mut preceding = vec![vec![]; n];
+    let mut following = vec![vec![]; n];
+    let mut waiting = vec![0; n];
+    for (i, j) in edges {
+        preceding[j].push(i);
+        following[i].push(j);
+        waiting[j] += 1;
+    }
+    // Always take the first node that's free to go, so ties stay in their original order
+    let mut ready: BinaryHeap<_> = (0..n).filter(|&i| waiting[i] == 0).map(Reverse).collect();
+    let mut placed = vec![false; n];
+    let mut sorted = Vec::with_capacity(n);
+    while let Some(Reverse(i)) = ready.pop() {
+        placed[i] = true;
+        sorted.push(nodes[i].clone());
+        for &j in &following[i] {
+            waiting[j] -= 1;
+            if waiting[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+    if sorted.len() == n {
+        return Ok(sorted);
+    }
+    // Everything left is waiting on something else that's left, so walking backwards has to end up going in circles
+    let mut path = vec![(0..n).find(|&i| !placed[i]).unwrap()];
+    loop {
+        let last = *path.last().unwrap();
+        let next = *preceding[last].iter().find(|&&p| !placed[p]).unwrap();
+        if let Some(start) = path.iter().position(|&i| i == next) {
+            // The path runs backwards, so flip it and close the loop
+            let cycle = path[start..]
+                .iter()
+                .rev()
+                .chain([&last])
+                .map(|&i| format!("{:?}", nodes[i]))
+                .join(" -> ");
+            return Err(anyhow!("Ordering has a cycle: {cycle}"));
+        }
+        path.push(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_toposort() -> Result<()> {
+        let sorted = toposort(&[5, 3, 4, 1, 2], |a, b| a % 2 == 1 && b % 2 == 0)?;
+        assert_eq!(vec![5, 3, 1, 4, 2], sorted);
+        Ok(())
+    }
+
+    #[test]
+    fn should_toposort_edges() -> Result<()> {
+        // A long chain given back to front, which is the worst case for rescanning
+        let nodes = (0..1000).collect_vec();
+        let edges = (1..1000).map(|i| (i, i - 1));
+        let sorted = toposort_edges(&nodes, edges)?;
+        assert_eq!((0..1000).rev().collect_vec(), sorted);
+        assert_eq!(vec!['a', 'b'], toposort_edges(&['a', 'b'], [])?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_find_cycles() {
+        let rules = [(1, 2), (2, 3), (3, 1)];
+        let err = toposort(&[4, 1, 2, 3], |a, b| rules.contains(&(*a, *b))).unwrap_err();
+        assert_eq!("Ordering has a cycle: 2 -> 3 -> 1 -> 2", err.to_string());
+    }
+}
@@ -0,0 +1,269 @@
+// Every so often a puzzle's answer is a row of letters drawn with '#' and '.'.
+// These are the two fonts AoC has used so far, as seen in the wild.
+use super::Point;
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+struct Font {
+    height: usize,
+    // Letters are laid out at a fixed pitch, with blank columns in between
+    stride: usize,
+    // Row by row, each as wide as the letter is. That's usually two columns short of the stride
+    // for the large font and one for the small, but the small Y fills the gap too.
+    glyphs: &'static [(char, &'static str)],
+}
+
+const SMALL: Font = Font {
+    height: 6,
+    stride: 5,
+    glyphs: &[
+        ('A', ".##.#..##..######..##..#"),
+        ('B', "###.#..####.#..##..####."),
+        ('C', ".##.#..##...#...#..#.##."),
+        ('E', "#####...###.#...#...####"),
+        ('F', "#####...###.#...#...#..."),
+        ('G', ".##.#..##...#.###..#.###"),
+        ('H', "#..##..######..##..##..#"),
+        ('I', ".###..#...#...#...#..###"),
+        ('J', "..##...#...#...##..#.##."),
+        ('K', "#..##.#.##..#.#.#.#.#..#"),
+        ('L', "#...#...#...#...#...####"),
+        ('O', ".##.#..##..##..##..#.##."),
+        ('P', "###.#..##..####.#...#..."),
+        ('R', "###.#..##..####.#.#.#..#"),
+        ('S', ".####...#....##....####."),
+        ('U', "#..##..##..##..##..#.##."),
+        ('Y', "#...##...#.#.#...#....#....#.."),
+        ('Z', "####...#..#..#..#...####"),
+    ],
+};
+
+const LARGE: Font = Font {
+    height: 10,
+    stride: 8,
+    glyphs: &[
+        (
+            'A',
+            "..##...#..#.#....##....##....########....##....##....##....#",
+        ),
+        (
+            'B',
+            "#####.#....##....##....######.#....##....##....##....######.",
+        ),
+        (
+            'C',
+            ".####.#....##.....#.....#.....#.....#.....#.....#....#.####.",
+        ),
+        (
+            'E',
+            "#######.....#.....#.....#####.#.....#.....#.....#.....######",
+        ),
+        (
+            'F',
+            "#######.....#.....#.....#####.#.....#.....#.....#.....#.....",
+        ),
+        (
+            'G',
+            ".####.#....##.....#.....#.....#..####....##....##...##.###.#",
+        ),
+        (
+            'H',
+            "#....##....##....##....########....##....##....##....##....#",
+        ),
+        (
+            'J',
+            "...###....#.....#.....#.....#.....#.....#.#...#.#...#..###..",
+        ),
+        (
+            'K',
+            "#....##...#.#..#..#.#...##....##....#.#...#..#..#...#.#....#",
+        ),
+        (
+            'L',
+            "#.....#.....#.....#.....#.....#.....#.....#.....#.....######",
+        ),
+        (
+            'N',
+            "#....###...###...##.#..##.#..##..#.##..#.##...###...###....#",
+        ),
+        (
+            'P',
+            "#####.#....##....##....######.#.....#.....#.....#.....#.....",
+        ),
+        (
+            'R',
+            "#####.#....##....##....######.#..#..#...#.#...#.#....##....#",
+        ),
+        (
+            'X',
+            "#....##....#.#..#..#..#...##....##...#..#..#..#.#....##....#",
+        ),
+        (
+            'Z',
+            "######.....#.....#....#....#....#....#....#.....#.....######",
+        ),
+    ],
+};
+
+fn is_lit(c: char) -> bool {
+    matches!(c, '#' | '█')
+}
+
+/// Reads letters out of ASCII art, e.g. the output of printing a grid of '#' and '.'.
+pub fn decode(art: &str) -> Result<String> {
+    let rows: Vec<Vec<bool>> = art
+        .lines()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.chars().map(is_lit).collect())
+        .collect();
+    decode_grid(&rows)
+}
+
+/// Reads letters out of a set of lit points, wherever they happen to be.
+pub fn decode_points<'a>(points: impl IntoIterator<Item = &'a Point>) -> Result<String> {
+    let points: Vec<_> = points.into_iter().collect();
+    let (Some(min_x), Some(min_y)) = (
+        points.iter().map(|p| p.x).min(),
+        points.iter().map(|p| p.y).min(),
+    ) else {
+        return Err(anyhow!("Nothing to read"));
+    };
+    let max_x = points.iter().map(|p| p.x).max().unwrap_or(min_x);
+    let max_y = points.iter().map(|p| p.y).max().unwrap_or(min_y);
+    let font = font_for(max_y - min_y + 1)?;
+    // The leftmost lit column isn't always where the first letter starts (I's first column is blank),
+    // so try each way of lining the points up with the letters
+    let shifted = |shift: usize| {
+        let mut rows = vec![vec![false; max_x - min_x + 1 + shift]; max_y - min_y + 1];
+        for p in &points {
+            rows[p.y - min_y][p.x - min_x + shift] = true;
+        }
+        rows
+    };
+    (0..font.stride)
+        .map(|shift| decode_grid(&shifted(shift)))
+        .find_or_first(Result::is_ok)
+        .unwrap_or_else(|| Err(anyhow!("Nothing to read")))
+}
+
+fn font_for(height: usize) -> Result<&'static Font> {
+    match height {
+        h if h == SMALL.height => Ok(&SMALL),
+        h if h == LARGE.height => Ok(&LARGE),
+        h => Err(anyhow!("No font is {h} rows tall")),
+    }
+}
+
+// Whether the letter starting at column `start` is `glyph`, with nothing lit in the gap after it
+fn matches(font: &Font, glyph: &str, rows: &[Vec<bool>], start: usize) -> bool {
+    let width = glyph.len() / font.height;
+    rows.iter().enumerate().all(|(y, row)| {
+        (0..font.stride).all(|dx| {
+            let lit = row.get(start + dx).copied().unwrap_or(false);
+            lit == (dx < width && glyph.as_bytes()[y * width + dx] == b'#')
+        })
+    })
+}
+
+pub fn decode_grid(rows: &[Vec<bool>]) -> Result<String> {
+    let font = font_for(rows.len())?;
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut decoded = String::new();
+    for (i, start) in (0..width).step_by(font.stride).enumerate() {
+        let Some((c, _)) = font
+            .glyphs
+            .iter()
+            .find(|(_, g)| matches(font, g, rows, start))
+        else {
+            return Err(anyhow!(
+                "Unrecognised letter {} (columns {start}-{})",
+                i + 1,
+                start + font.stride - 1
+            ));
+        };
+        decoded.push(*c);
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn should_decode_small_font() -> Result<()> {
+        let art = "\
+#..#.####.#....#.....##.
+#..#.#....#....#....#..#
+####.###..#....#....#..#
+#..#.#....#....#....#..#
+#..#.#....#....#....#..#
+#..#.####.####.####..##.";
+        assert_eq!("HELLO", decode(art)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_decode_large_font() -> Result<()> {
+        let art = "\
+#....#..######
+#....#..#.....
+.#..#...#.....
+.#..#...#.....
+..##....#####.
+..##....#.....
+.#..#...#.....
+.#..#...#.....
+#....#..#.....
+#....#..#.....";
+        assert_eq!("XF", decode(art)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_decode_points() -> Result<()> {
+        let art = "#...\n#...\n#...\n#...\n#...\n####";
+        let points: HashSet<Point> = art
+            .lines()
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.char_indices()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| Point::new(x + 10, y + 3))
+            })
+            .collect();
+        assert_eq!("L", decode_points(&points)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_line_points_up_with_letters() -> Result<()> {
+        let art = "\
+.###..##..#...#
+..#..#..#.#...#
+..#..#.....#.#.
+..#..#......#..
+..#..#..#...#..
+.###..##....#..";
+        let points: HashSet<Point> = art
+            .lines()
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.char_indices()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| Point::new(x + 7, y))
+            })
+            .collect();
+        assert_eq!("ICY", decode_points(&points)?);
+        assert_eq!("ICY", decode(art)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_unknown_letters() {
+        let art = "####\n####\n####\n####\n####\n####";
+        assert!(decode(art).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+// Some days keep a slow, obviously-correct version of something around next to the clever one.
+// This runs both over the same input and, if they disagree, whittles the input down to something small enough to debug by hand.
+use crate::{answer::Answer, input::AdventInput, solver::Solver};
+use anyhow::{anyhow, Result};
+use std::fmt::Display;
+
+/// A fast implementation and a reference one that should always give the same answer.
+pub struct CrossCheck<I> {
+    pub name: &'static str,
+    pub fast: fn(&I) -> Answer,
+    pub reference: fn(&I) -> Answer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub fast: Answer,
+    pub reference: Answer,
+    /// The smallest input we could find that still shows the problem
+    pub input: String,
+}
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "fast gave {}, reference gave {}, for input:\n{}",
+            self.fast, self.reference, self.input
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Agreed(Answer),
+    Diverged(Divergence),
+}
+
+/// Runs every one of the solver's cross-checks over `text`.
+pub fn run<S: Solver>(solver: &S, text: &str) -> Result<Vec<(&'static str, Verdict)>> {
+    let input = load(solver, text)?;
+    let mut verdicts = vec![];
+    for check in solver.crosschecks() {
+        let (fast, reference) = ((check.fast)(&input), (check.reference)(&input));
+        let verdict = match fast == reference {
+            true => Verdict::Agreed(fast),
+            false => Verdict::Diverged(diverge(solver, &check, text)),
+        };
+        verdicts.push((check.name, verdict));
+    }
+    Ok(verdicts)
+}
+
+/// For tests: panics with the minimized input if any cross-check disagrees.
+pub fn assert_agrees<S: Solver>(solver: &S, text: &str) {
+    let verdicts = run(solver, text).unwrap_or_else(|e| panic!("Couldn't load input: {e:#}"));
+    for (name, verdict) in verdicts {
+        if let Verdict::Diverged(d) = verdict {
+            panic!("{name} diverged: {d}");
+        }
+    }
+}
+
+fn load<S: Solver>(solver: &S, text: &str) -> Result<S::Input> {
+    solver.load(&AdventInput::from_text(text))
+}
+
+fn diverge<S: Solver>(solver: &S, check: &CrossCheck<S::Input>, text: &str) -> Divergence {
+    let still_diverges = |t: &str| {
+        load(solver, t).is_ok_and(|input| (check.fast)(&input) != (check.reference)(&input))
+    };
+    let input = minimize(text, still_diverges);
+    // Can't fail, minimize only keeps inputs that load
+    let loaded = load(solver, &input).expect("minimized input should still load");
+    Divergence {
+        fast: (check.fast)(&loaded),
+        reference: (check.reference)(&loaded),
+        input,
+    }
+}
+
+/// Throws away as much of `text` as it can while `fails` still holds, first whole lines and then single characters.
+pub fn minimize(text: &str, fails: impl Fn(&str) -> bool) -> String {
+    let lines: Vec<String> = text.lines().map(|l| l.to_owned()).collect();
+    let lines = shrink(lines, |ls| ls.join("\n"), &fails);
+    let chars: Vec<char> = lines.join("\n").chars().collect();
+    shrink(chars, |cs| cs.iter().collect(), &fails)
+        .iter()
+        .collect()
+}
+
+// Tries dropping chunks of decreasing size, keeping any removal that still fails
+fn shrink<T: Clone>(
+    mut units: Vec<T>,
+    join: impl Fn(&[T]) -> String,
+    fails: &impl Fn(&str) -> bool,
+) -> Vec<T> {
+    let mut chunk = units.len() / 2;
+    while chunk > 0 {
+        let mut start = 0;
+        while start < units.len() {
+            let end = (start + chunk).min(units.len());
+            let candidate = [&units[..start], &units[end..]].concat();
+            if fails(&join(&candidate)) {
+                units = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+        chunk /= 2;
+    }
+    units
+}
+
+pub fn ensure_agreed(verdicts: &[(&'static str, Verdict)]) -> Result<()> {
+    let diverged = verdicts
+        .iter()
+        .filter(|(_, v)| matches!(v, Verdict::Diverged(_)))
+        .count();
+    match diverged {
+        0 => Ok(()),
+        n => Err(anyhow!("{n} cross-check(s) diverged")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Adds up the numbers, except the "fast" version has a soft spot for sevens
+    struct Buggy;
+    impl Solver for Buggy {
+        const DAY: u8 = 0;
+        const TITLE: &'static str = "Buggy";
+        type Input = Vec<i64>;
+
+        fn new() -> Self {
+            Buggy
+        }
+
+        fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+            input.get_ints()
+        }
+
+        fn part_one(&self, input: &Self::Input) -> Result<Answer> {
+            Ok(input.iter().sum::<i64>().into())
+        }
+
+        fn part_two(&self, input: &Self::Input) -> Result<Answer> {
+            self.part_one(input)
+        }
+
+        fn crosschecks(&self) -> Vec<CrossCheck<Self::Input>> {
+            vec![CrossCheck {
+                name: "sum",
+                fast: |ints| {
+                    ints.iter()
+                        .map(|&i| if i == 7 { 8 } else { i })
+                        .sum::<i64>()
+                        .into()
+                },
+                reference: |ints| ints.iter().sum::<i64>().into(),
+            }]
+        }
+    }
+
+    #[test]
+    fn should_agree() -> Result<()> {
+        let verdicts = run(&Buggy, "1 2\n3 4")?;
+        assert_eq!(vec![("sum", Verdict::Agreed(Answer::Int(10)))], verdicts);
+        ensure_agreed(&verdicts)
+    }
+
+    #[test]
+    fn should_minimize_divergence() -> Result<()> {
+        let verdicts = run(&Buggy, "1 2\n3 4\n5 6 7 8\n9 10")?;
+        let expected = Divergence {
+            fast: Answer::Int(8),
+            reference: Answer::Int(7),
+            input: "7".to_owned(),
+        };
+        assert_eq!(vec![("sum", Verdict::Diverged(expected))], verdicts);
+        assert!(ensure_agreed(&verdicts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_minimize_lines_then_chars() {
+        let text = "abc\nxyz\nhello\n123";
+        assert_eq!("ll", minimize(text, |t| t.contains("ll")));
+    }
+}
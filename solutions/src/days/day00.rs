@@ -1,22 +1,27 @@
 // template for future days
-use crate::solver::Solver;
+use crate::{answer::Answer, input::AdventInput, solver::Solver};
 use anyhow::Result;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 0;
     const TITLE: &'static str = "UNKNOWN";
+    type Input = String;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        Ok(0)
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get()
     }
 
-    fn part_two(&self) -> Result<usize> {
-        Ok(0)
+    fn part_one(&self, _input: &Self::Input) -> Result<Answer> {
+        Ok(0.into())
+    }
+
+    fn part_two(&self, _input: &Self::Input) -> Result<Answer> {
+        Ok(0.into())
     }
 }
 
@@ -1,30 +1,40 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, io::BufRead, str::FromStr};
 
-use crate::solver::Solver;
-use anyhow::{anyhow, Result};
-use itertools::Itertools;
+use crate::{
+    answer::Answer,
+    input::{parse, AdventInput},
+    solver::Solver,
+};
+use anyhow::{Context, Result};
 
 pub struct Solution;
-impl Solver<isize, isize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 1;
     const TITLE: &'static str = "Historian Hysteria";
+    type Input = LocationLists;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<isize> {
-        let lists = self.input().get_as::<LocationLists>()?;
-        Ok(lists.get_total_distance())
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        LocationLists::from_reader(input.reader()?)
     }
 
-    fn part_two(&self) -> Result<isize> {
-        let lists = self.input().get_as::<LocationLists>()?;
-        Ok(lists.get_similarity_score())
+    fn part_one(&self, lists: &Self::Input) -> Result<Answer> {
+        Ok(lists.get_total_distance().into())
+    }
+
+    fn part_two(&self, lists: &Self::Input) -> Result<Answer> {
+        Ok(lists.get_similarity_score().into())
     }
 }
 
-struct LocationLists {
+// Any wider than this and counting into a Vec stops being worth the memory
+const DENSE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug)]
+pub struct LocationLists {
     left: Vec<isize>,
     right: Vec<isize>,
 }
@@ -32,31 +42,41 @@ impl FromStr for LocationLists {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut left = vec![];
-        let mut right = vec![];
-
-        for l in s.lines() {
-            let split = l.split_whitespace().collect_vec();
-            if split.len() != 2 {
-                return Err(anyhow!("Invalid list pair"));
+        Self::from_reader(s.as_bytes())
+    }
+}
+impl LocationLists {
+    // A line at a time, so even a huge input only ever exists as the two lists
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        let (mut left, mut right) = (vec![], vec![]);
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.with_context(|| format!("Couldn't read line {}", i + 1))?;
+            if line.trim().is_empty() {
+                continue;
             }
-            left.push(split[0].parse()?);
-            right.push(split[1].parse()?);
+            let (l, r) = parse::word_tuple::<(isize, isize)>(&line).map_err(|e| e.below(i))?;
+            left.push(l);
+            right.push(r);
         }
-
+        // Part one needs them in order and part two doesn't care, so do it once up front
+        left.sort_unstable();
+        right.sort_unstable();
         Ok(Self { left, right })
     }
-}
-impl LocationLists {
+
     fn get_total_distance(&self) -> isize {
         self.left
             .iter()
-            .sorted()
-            .zip(self.right.iter().sorted())
+            .zip(&self.right)
             .fold(0, |acc, (&l, &r)| acc + (l - r).abs())
     }
 
     fn get_similarity_score(&self) -> isize {
+        self.similarity_dense()
+            .unwrap_or_else(|| self.similarity_hashed())
+    }
+
+    fn similarity_hashed(&self) -> isize {
         let mut score = 0;
         let mut counts: HashMap<&isize, isize> = HashMap::new();
         for r in &self.right {
@@ -67,11 +87,34 @@ impl LocationLists {
         }
         score
     }
+
+    // Same thing, but counting into a Vec instead of hashing.  Only works if the right list is bunched up enough,
+    // which the real one is (it's all 5 digit numbers).
+    fn similarity_dense(&self) -> Option<isize> {
+        let (&min, &max) = (self.right.first()?, self.right.last()?);
+        let span = usize::try_from(max.checked_sub(min)?).ok()?;
+        if span >= DENSE_LIMIT {
+            return None;
+        }
+        let mut counts = vec![0; span + 1];
+        for r in &self.right {
+            counts[(r - min) as usize] += 1;
+        }
+        Some(
+            self.left
+                .iter()
+                .filter(|l| (min..=max).contains(l))
+                .map(|l| l * counts[(l - min) as usize])
+                .sum(),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "3   4
 4   3
@@ -91,6 +134,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_say_which_line_is_bad() {
+        let err = "1   2\n3   x".parse::<LocationLists>().unwrap_err();
+        assert_eq!("line 2, column 5: 'x' isn't a valid isize", err.to_string());
+        let err = "1   2\n\n3   4   5".parse::<LocationLists>().unwrap_err();
+        assert_eq!(
+            "line 3, column 9: expected 2 words, found 3",
+            err.to_string()
+        );
+    }
+
     #[test]
     fn should_get_distance() -> Result<()> {
         let example = "1    3\n7    4";
@@ -102,8 +156,8 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let test = EXAMPLE_INPUT.parse::<LocationLists>()?;
-        assert_eq!(11, test.get_total_distance());
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(11), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -112,13 +166,47 @@ mod tests {
         let example = "1 2\n2 3\n3 2";
         let test = example.parse::<LocationLists>()?;
         assert_eq!(7, test.get_similarity_score());
+        assert_eq!(Some(7), test.similarity_dense());
+        Ok(())
+    }
+
+    #[test]
+    fn should_only_count_densely_when_bunched_up() -> Result<()> {
+        let test = "1 2\n2 3000000".parse::<LocationLists>()?;
+        assert_eq!(None, test.similarity_dense());
+        assert_eq!(2, test.get_similarity_score());
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let test = EXAMPLE_INPUT.parse::<LocationLists>()?;
-        assert_eq!(31, test.get_similarity_score());
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(31), Solution::new().solve_part_two(&input)?);
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn should_parse_any_lists(pairs in prop::collection::vec((0..100_000isize, 0..100_000isize), 1..50)) {
+            let text = pairs.iter().map(|(l, r)| format!("{l}   {r}")).join("\n");
+            let lists = text.parse::<LocationLists>().unwrap();
+            let (left, right): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+            // They come back sorted
+            prop_assert_eq!(left.into_iter().sorted().collect_vec(), lists.left);
+            prop_assert_eq!(right.into_iter().sorted().collect_vec(), lists.right);
+        }
+
+        #[test]
+        fn should_count_the_same_either_way(
+            pairs in prop::collection::vec((-50..50isize, -50..50isize), 1..50),
+            spread in prop_oneof![Just(1), Just(1_000_000isize)],
+        ) {
+            let text = pairs.iter().map(|(l, r)| format!("{} {}", l * spread, r * spread)).join("\n");
+            let lists = text.parse::<LocationLists>().unwrap();
+            prop_assert_eq!(lists.similarity_hashed(), lists.get_similarity_score());
+            if spread == 1 {
+                prop_assert_eq!(Some(lists.similarity_hashed()), lists.similarity_dense());
+            }
+        }
+    }
 }
@@ -1,107 +1,296 @@
-use std::str::FromStr;
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
 
-use crate::solver::Solver;
+use crate::{
+    answer::Answer,
+    input::{
+        parse::{self, ParseError},
+        validate, AdventInput,
+    },
+    solver::Solver,
+};
 use anyhow::Result;
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 2;
     const TITLE: &'static str = "Red-Nosed Reports";
+    type Input = Vec<Report>;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let reports = self.input().get_lines_as::<Report>()?;
-        Ok(reports.iter().filter(|r| r.is_safe()).count())
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        // Not lines_with, which would skip blank lines; they're empty reports, which validate turns away
+        let read = input.get()?;
+        Ok(read
+            .lines()
+            .enumerate()
+            .map(|(i, l)| Report::parse(l).map_err(|e| e.below(i)))
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn validate(&self, reports: &Self::Input) -> Result<()> {
+        validate::non_empty(reports, "Input")?;
+        validate::each(reports, "Report", |r| {
+            validate::non_empty(&r.levels, "Level list")
+        })
+    }
+
+    fn part_one(&self, reports: &Self::Input) -> Result<Answer> {
+        Ok(reports
+            .iter()
+            .filter(|r| r.is_safe(&Rules::STRICT))
+            .count()
+            .into())
+    }
+
+    fn part_two(&self, reports: &Self::Input) -> Result<Answer> {
+        Ok(reports
+            .iter()
+            .filter(|r| r.is_safe(&Rules::DAMPENED))
+            .count()
+            .into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let reports = self.input().get_lines_as::<Report>()?;
-        Ok(reports.iter().filter(|r| r.is_safe_dampened()).count())
+    fn explain(&self, reports: &Self::Input) -> Result<String> {
+        Ok(explain(reports, &Rules::DAMPENED))
     }
 }
 
-enum LevelState {
-    Unsafe,
-    Safe(isize),
+/// What counts as safe: how far apart neighbouring levels can be, and how many levels the dampener can ignore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules {
+    pub step: RangeInclusive<isize>,
+    pub removals: usize,
 }
+impl Rules {
+    pub const STRICT: Rules = Rules {
+        step: 1..=3,
+        removals: 0,
+    };
+    pub const DAMPENED: Rules = Rules {
+        step: 1..=3,
+        removals: 1,
+    };
 
-struct Report {
+    fn allows(&self, left: isize, right: isize, direction: isize) -> bool {
+        self.step.contains(&((right - left) * direction))
+    }
+}
+
+pub struct Report {
     levels: Vec<isize>,
 }
 impl FromStr for Report {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let levels = s
-            .split_whitespace()
-            .filter_map(|x| x.parse().ok())
-            .collect_vec();
-        Ok(Self { levels })
+        Ok(Self::parse(s)?)
     }
 }
 impl Report {
-    fn is_safe(&self) -> bool {
-        are_safe(&self.levels)
+    fn parse(s: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            levels: parse::word_list(s)?,
+        })
     }
 
-    fn is_safe_dampened(&self) -> bool {
-        // This is what you get when the too-clever solution doesn't work for all edge cases
-        if are_safe(&self.levels) {
-            return true;
-        }
-        for n in 0..self.levels.len() {
-            let skipped: Vec<_> = self
-                .levels
-                .iter()
-                .enumerate()
-                .filter_map(|(i, &e)| (i != n).then_some(e))
-                .collect();
-            if are_safe(&skipped) {
-                return true;
-            }
+    fn is_safe(&self, rules: &Rules) -> bool {
+        self.fewest_removals(rules).is_some()
+    }
+
+    fn verdict(&self, rules: &Rules) -> Verdict {
+        let Some(failure) = self.first_failure(rules) else {
+            return Verdict::Safe;
+        };
+        match self.fewest_removals(rules) {
+            Some(removed) => Verdict::Dampened { removed, failure },
+            None => Verdict::Unsafe(failure),
         }
-        false
     }
-}
 
-fn are_safe(levels: &[isize]) -> bool {
-    let mut sign = 0;
-    let max = levels.len() - 1;
-    for i in 0..max {
-        let left = levels[i];
-        let right = levels[i + 1];
-        match check_diff(left, right, sign) {
-            LevelState::Unsafe => return false,
-            LevelState::Safe(ds) => {
-                if sign == 0 {
-                    sign = ds;
+    // The first pair of levels that breaks the rules, going by the direction the levels first move in
+    fn first_failure(&self, rules: &Rules) -> Option<Failure> {
+        let direction = self
+            .levels
+            .iter()
+            .tuple_windows()
+            .map(|(l, r)| (r - l).signum())
+            .find(|&d| d != 0)
+            .unwrap_or(1);
+        self.levels
+            .iter()
+            .tuple_windows()
+            .enumerate()
+            .find_map(|(at, (&left, &right))| {
+                let diff = right - left;
+                let problem = match diff * direction {
+                    _ if rules.allows(left, right, direction) => return None,
+                    0 => Problem::NoChange,
+                    d if d < 0 => Problem::Reversal,
+                    d if d > *rules.step.end() => Problem::TooBig,
+                    _ => Problem::TooSmall,
+                };
+                Some(Failure {
+                    at,
+                    left,
+                    right,
+                    problem,
+                })
+            })
+    }
+
+    // Which levels to drop (as few as possible) for the rest to be safe, if that's within the rules at all.
+    // For each level, work out the cheapest safe run that ends on it.  The previous level kept can only be
+    // so far back before we've dropped too many, so it's O(n·k) rather than trying every way to drop k levels.
+    fn fewest_removals(&self, rules: &Rules) -> Option<Vec<usize>> {
+        let (levels, n, k) = (&self.levels, self.levels.len(), rules.removals);
+        [1, -1]
+            .into_iter()
+            .filter_map(|direction| {
+                // The cost of the cheapest run ending on each level, and the level kept before it
+                let mut cheapest: Vec<Option<(usize, Option<usize>)>> = Vec::with_capacity(n);
+                for i in 0..n {
+                    // Starting here means dropping everything before it
+                    let start = (i <= k).then_some((i, None));
+                    let follow = (i.saturating_sub(k + 1)..i)
+                        .filter(|&j| rules.allows(levels[j], levels[i], direction))
+                        .filter_map(|j| cheapest[j].map(|(c, _)| (c + i - j - 1, Some(j))))
+                        .min();
+                    let best = start
+                        .into_iter()
+                        .chain(follow)
+                        .min()
+                        .filter(|&(c, _)| c <= k);
+                    cheapest.push(best);
                 }
-            }
+                // Anything after the last level kept gets dropped too
+                let (cost, last) = cheapest
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, c)| c.map(|(c, _)| (c + n - 1 - i, i)))
+                    .min()?;
+                (cost <= k).then(|| {
+                    let mut kept = vec![false; n];
+                    let mut at = Some(last);
+                    while let Some(i) = at {
+                        kept[i] = true;
+                        at = cheapest[i].and_then(|(_, previous)| previous);
+                    }
+                    (0..n).filter(|&i| !kept[i]).collect_vec()
+                })
+            })
+            .min_by_key(|removed| removed.len())
+    }
+
+    fn describe(&self, verdict: &Verdict) -> String {
+        let level = |i: usize| format!("level {} ({})", i + 1, self.levels[i]);
+        match verdict {
+            Verdict::Safe => "safe".to_string(),
+            Verdict::Dampened { removed, failure } => format!(
+                "safe without {}, otherwise {failure}",
+                removed.iter().map(|&i| level(i)).join(" and ")
+            ),
+            Verdict::Unsafe(failure) => format!("unsafe, {failure}"),
         }
     }
-    true
 }
 
-fn check_diff(left: isize, right: isize, sign: isize) -> LevelState {
-    let diff = right - left;
-    let d_sign = diff.signum();
-    match diff.abs() {
-        1..=3 => (),
-        _ => return LevelState::Unsafe,
-    }
-    if sign != 0 && sign != d_sign {
-        return LevelState::Unsafe;
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Problem {
+    TooBig,
+    TooSmall,
+    NoChange,
+    Reversal,
+}
+
+// Where a report first goes wrong
+#[derive(Debug, Clone, PartialEq)]
+struct Failure {
+    /// Index of the first level in the pair
+    at: usize,
+    left: isize,
+    right: isize,
+    problem: Problem,
+}
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let what = match self.problem {
+            Problem::TooBig => "is too big a jump",
+            Problem::TooSmall => "is too small a step",
+            Problem::NoChange => "doesn't change",
+            Problem::Reversal => "changes direction",
+        };
+        write!(
+            f,
+            "{} -> {} (levels {} and {}) {what}",
+            self.left,
+            self.right,
+            self.at + 1,
+            self.at + 2
+        )
     }
-    LevelState::Safe(d_sign)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Verdict {
+    Safe,
+    /// Only safe once the dampener drops these levels
+    Dampened {
+        removed: Vec<usize>,
+        failure: Failure,
+    },
+    Unsafe(Failure),
+}
+
+fn explain(reports: &[Report], rules: &Rules) -> String {
+    let verdicts = reports.iter().map(|r| r.verdict(rules)).collect_vec();
+    let mut out = reports
+        .iter()
+        .zip(&verdicts)
+        .enumerate()
+        .map(|(i, (r, v))| {
+            format!(
+                "Report {} ({}): {}",
+                i + 1,
+                r.levels.iter().join(" "),
+                r.describe(v)
+            )
+        })
+        .collect_vec();
+    let count = |f: fn(&Verdict) -> bool| verdicts.iter().filter(|v| f(v)).count();
+    out.push(format!(
+        "{} safe, {} safe with the dampener, {} unsafe",
+        count(|v| matches!(v, Verdict::Safe)),
+        count(|v| matches!(v, Verdict::Dampened { .. })),
+        count(|v| matches!(v, Verdict::Unsafe(_)))
+    ));
+    out.join("\n")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    // The obvious way: try every way of dropping up to k levels, and see if what's left is safe
+    fn brute_force(levels: &[isize], rules: &Rules) -> bool {
+        (0..=rules.removals.min(levels.len())).any(|r| {
+            (0..levels.len()).combinations(r).any(|dropped| {
+                let kept = (0..levels.len())
+                    .filter(|i| !dropped.contains(i))
+                    .map(|i| levels[i])
+                    .collect_vec();
+                [1, -1].into_iter().any(|direction| {
+                    kept.iter()
+                        .tuple_windows()
+                        .all(|(&l, &r)| rules.allows(l, r, direction))
+                })
+            })
+        })
+    }
 
     const EXAMPLE_INPUT: &str = "7 6 4 2 1
 1 2 7 8 9
@@ -118,109 +307,225 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_report_bad_level() {
+        let input = AdventInput::from_text("1 2 3\n7 6 x 2 1");
+        let Err(e) = Solution::new().load(&input) else {
+            panic!("Loaded a report with an x in it");
+        };
+        assert_eq!("line 2, column 5: 'x' isn't a valid isize", e.to_string());
+    }
+
     #[test]
     fn should_be_safe() -> Result<()> {
         let test = "1 2 3 4 5".parse::<Report>()?;
-        assert_eq!(true, test.is_safe());
+        assert!(test.is_safe(&Rules::STRICT));
         Ok(())
     }
 
     #[test]
     fn should_be_safe_negative() -> Result<()> {
         let test = "5 4 3 2 1".parse::<Report>()?;
-        assert_eq!(true, test.is_safe());
+        assert!(test.is_safe(&Rules::STRICT));
         Ok(())
     }
 
     #[test]
     fn should_detect_large_increase() -> Result<()> {
         let test = "1 10".parse::<Report>()?;
-        assert_eq!(false, test.is_safe());
+        assert!(!test.is_safe(&Rules::STRICT));
         Ok(())
     }
 
     #[test]
     fn should_detect_large_decrease() -> Result<()> {
         let test = "999 222".parse::<Report>()?;
-        assert_eq!(false, test.is_safe());
+        assert!(!test.is_safe(&Rules::STRICT));
         Ok(())
     }
 
     #[test]
     fn should_detect_change_in_direction() -> Result<()> {
         let test = "3 2 1 2 3".parse::<Report>()?;
-        assert_eq!(false, test.is_safe());
+        assert!(!test.is_safe(&Rules::STRICT));
         Ok(())
     }
 
     #[test]
     fn should_detect_no_change() -> Result<()> {
         let test = "1 2 3 3".parse::<Report>()?;
-        assert_eq!(false, test.is_safe());
+        assert!(!test.is_safe(&Rules::STRICT));
         Ok(())
     }
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let reports = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Report>().ok())
-            .collect_vec();
-        let safe_count = reports.iter().filter(|r| r.is_safe()).count();
-        assert_eq!(2, safe_count);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(2), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
     #[test]
     fn should_be_safe_dampened() -> Result<()> {
         let test = "3 1 2 4 6 9 10".parse::<Report>()?;
-        assert_eq!(true, test.is_safe_dampened());
+        assert!(test.is_safe(&Rules::DAMPENED));
         Ok(())
     }
 
     #[test]
     fn should_be_safe_dampened_change_direction() -> Result<()> {
         let test = "1 2 1 3".parse::<Report>()?;
-        assert_eq!(true, test.is_safe_dampened());
+        assert!(test.is_safe(&Rules::DAMPENED));
         Ok(())
     }
 
     #[test]
     fn should_be_safe_dampened_no_change() -> Result<()> {
         let test = "1 2 3 3".parse::<Report>()?;
-        assert_eq!(true, test.is_safe_dampened());
+        assert!(test.is_safe(&Rules::DAMPENED));
         Ok(())
     }
 
     #[test]
     fn should_detect_multiple_errors() -> Result<()> {
         let test = "1 2 5 3 4 3".parse::<Report>()?;
-        assert_eq!(false, test.is_safe_dampened());
+        assert!(!test.is_safe(&Rules::DAMPENED));
         Ok(())
     }
 
     #[test]
     fn should_detect_jump_at_start() -> Result<()> {
         let test = "1 5 6 7 8".parse::<Report>()?;
-        assert_eq!(true, test.is_safe_dampened());
+        assert!(test.is_safe(&Rules::DAMPENED));
         Ok(())
     }
 
     #[test]
     fn should_detect_multiple_errors_at_start() -> Result<()> {
         let test = "1 8 1 9 10".parse::<Report>()?;
-        assert_eq!(false, test.is_safe_dampened());
+        assert!(!test.is_safe(&Rules::DAMPENED));
+        Ok(())
+    }
+
+    #[test]
+    fn should_use_custom_rules() -> Result<()> {
+        let test = "1 2 3 9 4 10 5".parse::<Report>()?;
+        assert!(!test.is_safe(&Rules::DAMPENED));
+        let two = Rules {
+            removals: 2,
+            ..Rules::DAMPENED
+        };
+        assert!(test.is_safe(&two));
+        let bigger_steps = Rules {
+            step: 1..=6,
+            removals: 0,
+        };
+        assert!(!test.is_safe(&bigger_steps));
+        assert!("1 7 13 12".parse::<Report>()?.is_safe(&Rules {
+            removals: 1,
+            ..bigger_steps
+        }));
+        Ok(())
+    }
+
+    #[test]
+    fn should_say_what_went_wrong() -> Result<()> {
+        let problem = |levels: &str| -> Result<_> {
+            let report = levels.parse::<Report>()?;
+            Ok(report
+                .first_failure(&Rules::STRICT)
+                .map(|f| (f.at, f.problem)))
+        };
+        assert_eq!(None, problem("7 6 4 2 1")?);
+        assert_eq!(Some((1, Problem::TooBig)), problem("1 2 7 8 9")?);
+        assert_eq!(Some((1, Problem::Reversal)), problem("1 3 2 4 5")?);
+        assert_eq!(Some((2, Problem::NoChange)), problem("8 6 4 4 1")?);
+        let wide = Rules {
+            step: 2..=4,
+            removals: 0,
+        };
+        let report = "1 3 4".parse::<Report>()?;
+        assert_eq!(
+            "3 -> 4 (levels 2 and 3) is too small a step",
+            report.first_failure(&wide).unwrap().to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_say_which_level_was_removed() -> Result<()> {
+        let report = "1 3 2 4 5".parse::<Report>()?;
+        let verdict = report.verdict(&Rules::DAMPENED);
+        assert_eq!(
+            "safe without level 3 (2), otherwise 3 -> 2 (levels 2 and 3) changes direction",
+            report.describe(&verdict)
+        );
+        let report = "9 7 6 2 1".parse::<Report>()?;
+        assert!(matches!(
+            report.verdict(&Rules::DAMPENED),
+            Verdict::Unsafe(Failure {
+                at: 2,
+                problem: Problem::TooBig,
+                ..
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn should_explain_every_report() -> Result<()> {
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        let reports = Solution::new().load(&input)?;
+        let expected = "Report 1 (7 6 4 2 1): safe
+Report 2 (1 2 7 8 9): unsafe, 2 -> 7 (levels 2 and 3) is too big a jump
+Report 3 (9 7 6 2 1): unsafe, 6 -> 2 (levels 3 and 4) is too big a jump
+Report 4 (1 3 2 4 5): safe without level 3 (2), otherwise 3 -> 2 (levels 2 and 3) changes direction
+Report 5 (8 6 4 4 1): safe without level 4 (4), otherwise 4 -> 4 (levels 3 and 4) doesn't change
+Report 6 (1 3 6 7 9): safe
+2 safe, 2 safe with the dampener, 2 unsafe";
+        assert_eq!(expected, Solution::new().explain(&reports)?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let reports = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Report>().ok())
-            .collect_vec();
-        let dampened_count = reports.iter().filter(|r| r.is_safe_dampened()).count();
-        assert_eq!(4, dampened_count);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(4), Solution::new().solve_part_two(&input)?);
         Ok(())
     }
+
+    #[test]
+    fn should_reject_empty_reports() {
+        let input = AdventInput::from_text("7 6 4 2 1\n\n1 3 6 7 9");
+        assert!(Solution::new().solve_part_one(&input).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn should_parse_any_reports(levels in prop::collection::vec(prop::collection::vec(1..100isize, 1..10), 1..20)) {
+            let text = levels.iter().map(|l| l.iter().join(" ")).join("\n");
+            let reports = Solution::new().load(&AdventInput::from_text(text)).unwrap();
+            prop_assert_eq!(levels, reports.into_iter().map(|r| r.levels).collect_vec());
+        }
+
+        #[test]
+        fn should_agree_with_brute_force(
+            levels in prop::collection::vec(1..12isize, 1..9),
+            removals in 0..4usize,
+            step in prop_oneof![Just(1..=3), Just(0..=2), Just(2..=4)],
+        ) {
+            let rules = Rules { step, removals };
+            let report = Report { levels: levels.clone() };
+            prop_assert_eq!(brute_force(&levels, &rules), report.is_safe(&rules));
+            // Whatever it says to drop really is the fix
+            if let Some(removed) = report.fewest_removals(&rules) {
+                let kept = Report {
+                    levels: (0..levels.len()).filter(|i| !removed.contains(i)).map(|i| levels[i]).collect(),
+                };
+                prop_assert!(removed.len() <= removals);
+                let strict = Rules { removals: 0, ..rules };
+                prop_assert!(kept.first_failure(&strict).is_none());
+            }
+        }
+    }
 }
@@ -1,83 +1,241 @@
-use crate::solver::Solver;
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::{answer::Answer, input::AdventInput, solver::Solver};
 use anyhow::Result;
+use itertools::Itertools;
 use regex::Regex;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 03;
     const TITLE: &'static str = "Mull It Over";
+    type Input = Vec<Instruction>;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let instructions = find_instructions(&self.input().get()?)?;
-        Ok(execute(&instructions, true))
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        Ok(Interpreter::new(&STANDARD)?.find(&input.get()?))
+    }
+
+    fn part_one(&self, instructions: &Self::Input) -> Result<Answer> {
+        Ok(execute(instructions, true).into())
+    }
+
+    fn part_two(&self, instructions: &Self::Input) -> Result<Answer> {
+        Ok(execute(instructions, false).into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let instructions = find_instructions(&self.input().get()?)?;
-        Ok(execute(&instructions, false))
+    fn explain(&self, instructions: &Self::Input) -> Result<String> {
+        Ok(trace(instructions, false).iter().join("\n"))
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Instruction {
-    Mul(usize, usize),
-    Do,
-    DoNot,
+#[derive(Debug)]
+pub struct Machine {
+    pub enabled: bool,
+    pub total: isize,
+}
+
+/// Something the corrupted memory might be trying to say.
+#[derive(Debug)]
+pub struct Op {
+    pub name: &'static str,
+    /// How many numbers it takes
+    pub arity: RangeInclusive<usize>,
+    /// Whether `don't()` switches it off
+    pub conditional: bool,
+    pub run: fn(&mut Machine, &[isize]),
 }
 
-fn find_instructions(s: &str) -> Result<Vec<Instruction>> {
-    let mut instr = vec![];
-    let finder = Regex::new(r"(?:do\(\)|don't\(\)|mul\((\d+),(\d+)\))")?;
-    for caps in finder.captures_iter(s) {
-        match &caps[0] {
-            "do()" => instr.push(Instruction::Do),
-            "don't()" => instr.push(Instruction::DoNot),
-            _ => {
-                let left = &caps[1];
-                let right = &caps[2];
-                instr.push(Instruction::Mul(left.parse()?, right.parse()?));
-            }
+pub static MUL: Op = Op {
+    name: "mul",
+    arity: 2..=2,
+    conditional: true,
+    run: |m, args| m.total += args[0] * args[1],
+};
+pub static DO: Op = Op {
+    name: "do",
+    arity: 0..=0,
+    conditional: false,
+    run: |m, _| m.enabled = true,
+};
+pub static DONT: Op = Op {
+    name: "don't",
+    arity: 0..=0,
+    conditional: false,
+    run: |m, _| m.enabled = false,
+};
+pub static ADD: Op = Op {
+    name: "add",
+    arity: 1..=usize::MAX,
+    conditional: true,
+    run: |m, args| m.total += args.iter().sum::<isize>(),
+};
+pub static SUB: Op = Op {
+    name: "sub",
+    arity: 2..=2,
+    conditional: true,
+    run: |m, args| m.total += args[0] - args[1],
+};
+
+// What the puzzle actually uses
+pub static STANDARD: [&Op; 3] = [&MUL, &DO, &DONT];
+pub static EXTENDED: [&Op; 5] = [&MUL, &DO, &DONT, &ADD, &SUB];
+
+pub struct Instruction {
+    op: &'static Op,
+    args: Vec<isize>,
+    /// Byte offset into the memory, for the trace
+    at: usize,
+}
+impl PartialEq for Instruction {
+    fn eq(&self, other: &Self) -> bool {
+        (self.op.name, &self.args, self.at) == (other.op.name, &other.args, other.at)
+    }
+}
+impl std::fmt::Debug for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self} at {}", self.at)
+    }
+}
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.op.name, self.args.iter().join(","))
+    }
+}
+
+pub struct Interpreter {
+    ops: Vec<&'static Op>,
+    max_digits: usize,
+    finder: Regex,
+}
+impl Interpreter {
+    pub fn new(ops: &[&'static Op]) -> Result<Self> {
+        // Anything that looks like a call to one of the ops; whether the arguments make sense gets checked afterwards
+        let names = ops.iter().map(|op| regex::escape(op.name)).join("|");
+        Ok(Self {
+            ops: ops.to_vec(),
+            max_digits: 3,
+            finder: Regex::new(&format!(r"({names})\(([^()]*)\)"))?,
+        })
+    }
+
+    pub fn max_digits(mut self, max_digits: usize) -> Self {
+        self.max_digits = max_digits;
+        self
+    }
+
+    pub fn find(&self, memory: &str) -> Vec<Instruction> {
+        self.finder
+            .captures_iter(memory)
+            .filter_map(|caps| {
+                let op = *self.ops.iter().find(|op| op.name == &caps[1])?;
+                let args = self.args(&caps[2])?;
+                op.arity.contains(&args.len()).then(|| Instruction {
+                    op,
+                    args,
+                    at: caps.get(0).unwrap().start(),
+                })
+            })
+            .collect()
+    }
+
+    // Only plain numbers of a few digits count; anything else is just more corruption
+    fn args(&self, s: &str) -> Option<Vec<isize>> {
+        if s.is_empty() {
+            return Some(vec![]);
+        }
+        s.split(',')
+            .map(|a| {
+                let valid = (1..=self.max_digits).contains(&a.len())
+                    && a.bytes().all(|b| b.is_ascii_digit());
+                valid.then(|| a.parse().ok()).flatten()
+            })
+            .collect()
+    }
+}
+
+pub struct Step<'a> {
+    pub instruction: &'a Instruction,
+    /// False if it was skipped because it's been switched off
+    pub ran: bool,
+    pub was_enabled: bool,
+    pub enabled: bool,
+    pub total: isize,
+}
+impl Display for Step<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>6}  {}: ", self.instruction.at, self.instruction)?;
+        match (self.ran, self.was_enabled, self.enabled) {
+            (false, _, _) => write!(f, "skipped, disabled"),
+            (true, false, true) => write!(f, "now enabled"),
+            (true, true, false) => write!(f, "now disabled"),
+            (true, _, _) => write!(f, "total {}", self.total),
         }
     }
-    Ok(instr)
 }
 
-fn execute(instr: &[Instruction], skip_conditionals: bool) -> usize {
-    let mut do_mult = true;
-    let mut total = 0;
-    for i in instr {
-        match i {
-            Instruction::Mul(l, r) => {
-                if skip_conditionals || do_mult {
-                    total += l * r
-                }
-            }
-            Instruction::Do => do_mult = true,
-            Instruction::DoNot => do_mult = false,
+// Part one doesn't know about switching things off yet, so it ignores it
+fn run<'a>(
+    instructions: &'a [Instruction],
+    ignore_conditionals: bool,
+    mut on_step: impl FnMut(Step<'a>),
+) -> isize {
+    let mut machine = Machine {
+        enabled: true,
+        total: 0,
+    };
+    for i in instructions {
+        let was_enabled = machine.enabled;
+        let ran = ignore_conditionals || !i.op.conditional || machine.enabled;
+        if ran {
+            (i.op.run)(&mut machine, &i.args);
         }
+        on_step(Step {
+            instruction: i,
+            ran,
+            was_enabled,
+            enabled: machine.enabled,
+            total: machine.total,
+        });
     }
-    total
+    machine.total
+}
+
+fn execute(instructions: &[Instruction], ignore_conditionals: bool) -> isize {
+    run(instructions, ignore_conditionals, |_| ())
+}
+
+fn trace(instructions: &[Instruction], ignore_conditionals: bool) -> Vec<Step<'_>> {
+    let mut steps = vec![];
+    run(instructions, ignore_conditionals, |s| steps.push(s));
+    steps
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str =
         "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
 
+    fn find_instructions(s: &str) -> Result<Vec<Instruction>> {
+        Ok(Interpreter::new(&STANDARD)?.find(s))
+    }
+
+    fn names(instructions: &[Instruction]) -> Vec<String> {
+        instructions.iter().map(|i| i.to_string()).collect()
+    }
+
     #[test]
     fn should_find_multiplication() -> Result<()> {
         let test = "xyzmul(12,34)abcmul(56,78)pdq";
         let found = find_instructions(test)?;
-        assert_eq!(
-            vec![Instruction::Mul(12, 34), Instruction::Mul(56, 78)],
-            found
-        );
+        assert_eq!(vec!["mul(12,34)", "mul(56,78)"], names(&found));
+        assert_eq!(vec![3, 16], found.iter().map(|i| i.at).collect_vec());
         Ok(())
     }
 
@@ -92,9 +250,8 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let instructions = find_instructions(EXAMPLE_INPUT)?;
-        let sum = execute(&instructions, true);
-        assert_eq!(161, sum);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(161), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -105,7 +262,7 @@ mod tests {
     fn should_find_other_instructions() -> Result<()> {
         let test = "abcdo()xyzdon't()";
         let found = find_instructions(test)?;
-        assert_eq!(vec![Instruction::Do, Instruction::DoNot], found);
+        assert_eq!(vec!["do()", "don't()"], names(&found));
         Ok(())
     }
 
@@ -114,14 +271,8 @@ mod tests {
         let test = "mul(1,2)don't()mul(3,4)do()mul(5,6)";
         let ins = find_instructions(test)?;
         assert_eq!(
-            vec![
-                Instruction::Mul(1, 2),
-                Instruction::DoNot,
-                Instruction::Mul(3, 4),
-                Instruction::Do,
-                Instruction::Mul(5, 6)
-            ],
-            ins
+            vec!["mul(1,2)", "don't()", "mul(3,4)", "do()", "mul(5,6)"],
+            names(&ins)
         );
 
         let sum = execute(&ins, false);
@@ -131,9 +282,56 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let instructions = find_instructions(EXAMPLE_INPUT_PART2)?;
-        let sum = execute(&instructions, false);
-        assert_eq!(48, sum);
+        let input = AdventInput::from_text(EXAMPLE_INPUT_PART2);
+        assert_eq!(Answer::from(48), Solution::new().solve_part_two(&input)?);
         Ok(())
     }
+
+    #[test]
+    fn should_validate_arguments() -> Result<()> {
+        let test = "mul(1234,5)mul(12,3)mul(1,2,3)mul(,1)mul( 1,2)mul(-1,2)do(1)don't()";
+        assert_eq!(
+            vec!["mul(12,3)", "don't()"],
+            names(&find_instructions(test)?)
+        );
+        let found = Interpreter::new(&STANDARD)?.max_digits(4).find(test);
+        assert_eq!(vec!["mul(1234,5)", "mul(12,3)", "don't()"], names(&found));
+        Ok(())
+    }
+
+    #[test]
+    fn should_run_extra_ops() -> Result<()> {
+        let test = "add(1,2,3)mul(2,3)sub(1,10)don't()add(100)do()sub(5,0)";
+        let found = Interpreter::new(&EXTENDED)?.find(test);
+        assert_eq!(7, found.len());
+        assert_eq!(8, execute(&found, false));
+        assert_eq!(108, execute(&found, true));
+        // The standard set doesn't know about them
+        assert_eq!(
+            vec!["mul(2,3)", "don't()", "do()"],
+            names(&find_instructions(test)?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_trace_execution() -> Result<()> {
+        let ins = find_instructions(EXAMPLE_INPUT_PART2)?;
+        let expected = "     1  mul(2,4): total 8
+    20  don't(): now disabled
+    28  mul(5,5): skipped, disabled
+    48  mul(11,8): skipped, disabled
+    59  do(): now enabled
+    64  mul(8,5): total 48";
+        assert_eq!(expected, Solution::new().explain(&ins)?);
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn should_not_panic_running_garbage(s in "(mul|add|sub|do|don't|[(),0-9x])*") {
+            let found = Interpreter::new(&EXTENDED).unwrap().find(&s);
+            execute(&found, false);
+        }
+    }
 }
@@ -1,26 +1,29 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::{common::IPoint, solver::Solver};
+use crate::{answer::Answer, common::IPoint, input::AdventInput, solver::Solver};
 use anyhow::Result;
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 04;
     const TITLE: &'static str = "Ceres Search";
+    type Input = WordSearch;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let word_search = self.input().get_as::<WordSearch>()?;
-        Ok(word_search.count_occurrences("XMAS"))
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_as::<WordSearch>()
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let word_search = self.input().get_as::<WordSearch>()?;
-        Ok(word_search.count_x_mas())
+    fn part_one(&self, word_search: &Self::Input) -> Result<Answer> {
+        Ok(word_search.count_occurrences("XMAS").into())
+    }
+
+    fn part_two(&self, word_search: &Self::Input) -> Result<Answer> {
+        Ok(word_search.count_x_mas().into())
     }
 }
 
@@ -38,7 +41,7 @@ const OFFSETS: [(isize, isize); 8] = [
 const CROSS_OFFSETS: [(isize, isize); 4] = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
 
 #[derive(Debug)]
-struct WordSearch {
+pub struct WordSearch {
     max_x: isize,
     max_y: isize,
     letters: HashMap<IPoint, char>,
@@ -58,7 +61,7 @@ impl FromStr for WordSearch {
                     max_x = x;
                 }
             }
-            max_y = y as isize;
+            max_y = y;
         }
         Ok(Self {
             letters,
@@ -89,7 +92,7 @@ impl WordSearch {
     }
 
     fn search(&self, start: IPoint, offset: (isize, isize), needle: &[char]) -> bool {
-        if needle.len() == 0 {
+        if needle.is_empty() {
             // we've run out of characters to look for, so we must have found the word!
             return true;
         }
@@ -110,7 +113,7 @@ impl WordSearch {
                     if c == &'A' {
                         let mut mas_count = 0;
                         for o in CROSS_OFFSETS {
-                            if self.search(p + o, (o.0 * -1, o.1 * -1), &XMAS[1..]) {
+                            if self.search(p + o, (-o.0, -o.1), &XMAS[1..]) {
                                 mas_count += 1;
                             }
                         }
@@ -180,9 +183,8 @@ MXMXAXMASX";
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let search = EXAMPLE_INPUT.parse::<WordSearch>()?;
-        let count = search.count_occurrences("XMAS");
-        assert_eq!(18, count);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(18), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -197,9 +199,8 @@ MXMXAXMASX";
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let search = EXAMPLE_INPUT.parse::<WordSearch>()?;
-        let count = search.count_x_mas();
-        assert_eq!(9, count);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(9), Solution::new().solve_part_two(&input)?);
         Ok(())
     }
 }
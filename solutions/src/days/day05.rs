@@ -1,36 +1,61 @@
 use std::{
+    cmp::Reverse,
     collections::{HashMap, HashSet},
+    fmt::Display,
     str::FromStr,
-    usize,
 };
 
-use crate::solver::Solver;
-use anyhow::{anyhow, Result};
+use crate::{
+    answer::Answer,
+    common::toposort_edges,
+    input::{parse, validate, AdventInput},
+    solver::Solver,
+};
+use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
+use rand::prelude::*;
+use tracing::trace;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 05;
     const TITLE: &'static str = "Print Queue";
+    type Input = PrintOrder;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let order = self.input().get_as::<PrintOrder>()?;
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_as::<PrintOrder>()
+    }
+
+    fn validate(&self, order: &Self::Input) -> Result<()> {
+        validate::each(&order.pages, "Update", |u| {
+            validate::non_empty(u, "Page list")
+        })
+    }
+
+    fn explain(&self, order: &Self::Input) -> Result<String> {
+        order.explain()
+    }
+
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        Ok(generate(size, rng))
+    }
+
+    fn part_one(&self, order: &Self::Input) -> Result<Answer> {
         let checksum = order.get_valid_checksum();
-        Ok(checksum)
+        Ok(checksum.into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let order = self.input().get_as::<PrintOrder>()?;
-        let checksum = order.get_fixed_checksum();
-        Ok(checksum)
+    fn part_two(&self, order: &Self::Input) -> Result<Answer> {
+        let checksum = order.get_fixed_checksum()?;
+        Ok(checksum.into())
     }
 }
 
-struct PrintOrder {
+pub struct PrintOrder {
     precedence: HashMap<usize, HashSet<usize>>,
     pages: Vec<Vec<usize>>,
 }
@@ -39,21 +64,14 @@ impl FromStr for PrintOrder {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let mut precedence = HashMap::new();
-        let mut pages = vec![];
-        let [order_rules, page_updates] = s.split("\n\n").collect_vec()[..2] else {
+        let [order_rules, page_updates] = parse::sections(s)[..] else {
             return Err(anyhow!("Invalid print order specification"));
         };
-        for l in order_rules.lines() {
-            let [before, after] = l.split('|').collect_vec()[..2] else {
-                return Err(anyhow!("Invalid page order rule"));
-            };
-            let (before, after): (usize, usize) = (before.parse()?, after.parse()?);
+        for (before, after) in order_rules.lines_with(|l| parse::split_tuple(l, "|"))? {
             let rules = precedence.entry(after).or_insert(HashSet::new());
             rules.insert(before);
         }
-        for l in page_updates.lines() {
-            pages.push(l.split(',').filter_map(|s| s.parse().ok()).collect_vec());
-        }
+        let pages = page_updates.lines_with(|l| parse::split_list(l, ","))?;
         Ok(Self { precedence, pages })
     }
 }
@@ -69,72 +87,184 @@ impl PrintOrder {
     }
 
     fn is_valid(&self, update: &[usize]) -> bool {
+        self.violations(update).is_empty()
+    }
+
+    // Every rule the update breaks, in the order they turn up
+    fn violations(&self, update: &[usize]) -> Vec<Violation> {
         let indexed: HashMap<usize, usize> =
             HashMap::from_iter(update.iter().enumerate().map(|(k, &v)| (v, k)));
-        for (idx, page) in update.iter().enumerate() {
+        let mut found = vec![];
+        for (idx, &page) in update.iter().enumerate() {
             let Some(precedents) = self.precedence.get(&page) else {
                 continue;
             };
-            for p in precedents {
-                let Some(&pi) = indexed.get(p) else {
+            for &p in precedents {
+                let Some(&pi) = indexed.get(&p) else {
                     continue;
                 };
                 if pi > idx {
-                    return false;
+                    found.push(Violation {
+                        before: p,
+                        after: page,
+                        before_at: pi,
+                        after_at: idx,
+                    });
                 }
             }
         }
-        true
+        found.sort_by_key(|v| (v.after_at, v.before_at));
+        found
     }
 
-    fn fix_page_order(&self) -> Vec<Vec<usize>> {
+    fn fix(&self, update: &[usize]) -> Result<Vec<usize>> {
+        // Only the rules between pages in this update matter
+        let index: &HashMap<usize, usize> =
+            &update.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+        let edges = update.iter().enumerate().flat_map(|(j, page)| {
+            let precedents = self.precedence.get(page).into_iter().flatten();
+            // A page can't come before itself, whatever the rules say
+            precedents
+                .filter_map(move |p| index.get(p).filter(|&&i| i != j))
+                .map(move |&i| (i, j))
+        });
+        toposort_edges(update, edges).with_context(|| format!("Can't fix update {update:?}"))
+    }
+
+    fn fix_page_order(&self) -> Result<Vec<Vec<usize>>> {
         let mut fixed_updates = vec![];
-        for update in &self.pages {
-            let mut needs_update = false;
-            let mut updated = update.clone();
-            let mut i = 0;
-            // I was never good at sorting algorithms.
-            // This is probably inefficient but it's what got me the star so...
-            while i < updated.len() {
-                let mut modified = false;
-                let page = updated[i];
-                let Some(precedents) = self.precedence.get(&page) else {
-                    i += 1;
-                    continue;
-                };
-                for j in (i + 1..updated.len()).rev() {
-                    if precedents.contains(&updated[j]) {
-                        modified = true;
-                        updated.remove(i);
-                        updated.insert(j, page);
-                        break;
-                    }
-                }
-                if !modified {
-                    i += 1;
-                } else {
-                    needs_update = true;
-                }
-            }
-            if needs_update {
-                fixed_updates.push(updated);
+        for update in self.pages.iter().filter(|u| !self.is_valid(u)) {
+            let fixed = self.fix(update)?;
+            trace!("Fixed {update:?} to {fixed:?}");
+            fixed_updates.push(fixed);
+        }
+        Ok(fixed_updates)
+    }
+
+    fn explain(&self) -> Result<String> {
+        let mut out = vec![];
+        let mut broken = 0;
+        for (i, update) in self.pages.iter().enumerate() {
+            let violations = self.violations(update);
+            if violations.is_empty() {
+                continue;
             }
+            broken += 1;
+            out.push(format!(
+                "Update {} ({}) is out of order:",
+                i + 1,
+                update.iter().join(",")
+            ));
+            out.extend(violations.iter().map(|v| format!("  {v}")));
+            let fixed = self.fix(update)?;
+            out.push(format!("  fixed to {}:", fixed.iter().join(",")));
+            out.extend(moves(update, &fixed).iter().map(|m| format!("    {m}")));
         }
-        fixed_updates
+        out.push(format!(
+            "{broken} of {} updates were out of order",
+            self.pages.len()
+        ));
+        Ok(out.join("\n"))
     }
 
-    fn get_fixed_checksum(&self) -> usize {
+    fn get_fixed_checksum(&self) -> Result<usize> {
         let mut checksum = 0;
-        for f in self.fix_page_order() {
+        for f in self.fix_page_order()? {
             checksum += f[f.len() / 2];
         }
-        checksum
+        Ok(checksum)
+    }
+}
+#[derive(Debug, PartialEq)]
+struct Violation {
+    before: usize,
+    after: usize,
+    before_at: usize,
+    after_at: usize,
+}
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}|{} is broken: {} is at index {}, after {} at index {}",
+            self.before, self.after, self.before, self.before_at, self.after, self.after_at
+        )
     }
 }
 
+// Indices shift around as pages come and go, so say where a page goes by what it lands next to
+#[derive(Debug, PartialEq)]
+struct Move {
+    page: usize,
+    /// The page it goes straight after, or None for the front
+    after: Option<usize>,
+}
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.after {
+            Some(after) => write!(f, "move {} after {after}", self.page),
+            None => write!(f, "move {} to the front", self.page),
+        }
+    }
+}
+
+// The fewest pages that have to be picked up and put back somewhere else to get from `update` to `fixed`.
+// Whatever's in the longest run already in the right relative order can stay put; everything else moves.
+// They're in the order to make them, so a page only ever goes after one that's already where it belongs.
+fn moves(update: &[usize], fixed: &[usize]) -> Vec<Move> {
+    let target: HashMap<usize, usize> = fixed.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+    let targets = update.iter().map(|p| target[p]).collect_vec();
+    // Longest increasing subsequence, keeping track of where each one came from
+    let mut longest = vec![1; targets.len()];
+    let mut previous = vec![None; targets.len()];
+    for i in 0..targets.len() {
+        for j in 0..i {
+            if targets[j] < targets[i] && longest[j] + 1 > longest[i] {
+                longest[i] = longest[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut staying = HashSet::new();
+    let mut at = (0..targets.len()).max_by_key(|&i| (longest[i], Reverse(i)));
+    while let Some(i) = at {
+        staying.insert(i);
+        at = previous[i];
+    }
+    (0..update.len())
+        .filter(|i| !staying.contains(i))
+        .sorted_by_key(|&i| targets[i])
+        .map(|i| Move {
+            page: update[i],
+            after: targets[i].checked_sub(1).map(|t| fixed[t]),
+        })
+        .collect()
+}
+
+// `size` updates drawn from 49 pages, like the real input.  Every pair of pages gets a rule, so every update has exactly one right order.
+fn generate(size: usize, rng: &mut StdRng) -> String {
+    let mut pages = (10..100).collect_vec();
+    pages.shuffle(rng);
+    pages.truncate(49);
+    let rules = pages
+        .iter()
+        .tuple_combinations()
+        .map(|(before, after)| format!("{before}|{after}"))
+        .collect_vec();
+    let updates = (0..size)
+        .map(|_| {
+            // Always odd, so there's a middle page
+            let len = rng.random_range(2..=11) * 2 + 1;
+            pages.sample(rng, len).join(",")
+        })
+        .collect_vec();
+    format!("{}\n\n{}", rules.join("\n"), updates.join("\n"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "47|53
 97|13
@@ -176,6 +306,14 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_report_bad_page() {
+        let Err(e) = "1|2\n\n1,2\n1,2x,3".parse::<PrintOrder>() else {
+            panic!("Parsed an update with a 2x in it");
+        };
+        assert_eq!("line 4, column 3: '2x' isn't a valid usize", e.to_string());
+    }
+
     #[test]
     fn should_be_valid() -> Result<()> {
         let order = "1|2\n\n1,2,3".parse::<PrintOrder>()?;
@@ -194,16 +332,15 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let order = EXAMPLE_INPUT.parse::<PrintOrder>()?;
-        let checksum = order.get_valid_checksum();
-        assert_eq!(143, checksum);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(143), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
     #[test]
     fn should_update_order() -> Result<()> {
         let order = "1|2\n2|3\n1|3\n\n3,2,1".parse::<PrintOrder>()?;
-        let fixed = order.fix_page_order();
+        let fixed = order.fix_page_order()?;
         assert_eq!(vec![vec![1, 2, 3]], fixed);
         Ok(())
     }
@@ -211,7 +348,7 @@ mod tests {
     #[test]
     fn should_get_fixed_checksum() -> Result<()> {
         let order = "1|2\n2|3\n1|3\n\n3,2,1\n1,3,2".parse::<PrintOrder>()?;
-        let checksum = order.get_fixed_checksum();
+        let checksum = order.get_fixed_checksum()?;
         assert_eq!(4, checksum);
         Ok(())
     }
@@ -219,16 +356,119 @@ mod tests {
     #[test]
     fn should_skip_valid() -> Result<()> {
         let order = "1|2\n2|3\n1|3\n\n3,2,1\n1,2,3".parse::<PrintOrder>()?;
-        let checksum = order.get_fixed_checksum();
+        let checksum = order.get_fixed_checksum()?;
         assert_eq!(2, checksum);
         Ok(())
     }
 
     #[test]
-    fn should_solve_part2() -> Result<()> {
+    fn should_reject_contradictory_rules() -> Result<()> {
+        let order = "1|2\n2|3\n3|1\n\n1,2,3".parse::<PrintOrder>()?;
+        let err = order.get_fixed_checksum().unwrap_err();
+        assert_eq!(
+            "Can't fix update [1, 2, 3]: Ordering has a cycle: 2 -> 3 -> 1 -> 2",
+            format!("{err:#}")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_list_violations() -> Result<()> {
         let order = EXAMPLE_INPUT.parse::<PrintOrder>()?;
-        let checksum = order.get_fixed_checksum();
-        assert_eq!(123, checksum);
+        assert!(order.violations(&order.pages[0]).is_empty());
+        assert_eq!(
+            vec![Violation {
+                before: 97,
+                after: 75,
+                before_at: 1,
+                after_at: 0
+            }],
+            order.violations(&order.pages[3])
+        );
+        assert_eq!(
+            "97|75 is broken: 97 is at index 1, after 75 at index 0",
+            order.violations(&order.pages[3])[0].to_string()
+        );
+        Ok(())
+    }
+
+    fn apply(update: &[usize], moves: &[Move]) -> Vec<usize> {
+        let mut pages = update.to_vec();
+        for m in moves {
+            pages.retain(|&p| p != m.page);
+            let at = m
+                .after
+                .map_or(0, |a| pages.iter().position(|&p| p == a).unwrap() + 1);
+            pages.insert(at, m.page);
+        }
+        pages
+    }
+
+    #[test]
+    fn should_find_fewest_moves() {
+        assert_eq!(
+            vec![Move {
+                page: 97,
+                after: None
+            }],
+            moves(&[75, 97, 47, 61, 53], &[97, 75, 47, 61, 53])
+        );
+        // 97, 75 and 29 are already in the right order, so only the other two have to move
+        let update = [97, 13, 75, 29, 47];
+        let fixed = [97, 75, 47, 29, 13];
+        let found = moves(&update, &fixed);
+        assert_eq!(
+            vec!["move 47 after 75", "move 13 after 29"],
+            found.iter().map(|m| m.to_string()).collect_vec()
+        );
+        assert_eq!(fixed.to_vec(), apply(&update, &found));
+        assert!(moves(&[1, 2, 3], &[1, 2, 3]).is_empty());
+    }
+
+    #[test]
+    fn should_explain() -> Result<()> {
+        let order = "1|2\n2|3\n1|3\n\n1,2,3\n3,2,1".parse::<PrintOrder>()?;
+        let expected = "Update 2 (3,2,1) is out of order:
+  2|3 is broken: 2 is at index 1, after 3 at index 0
+  1|3 is broken: 1 is at index 2, after 3 at index 0
+  1|2 is broken: 1 is at index 2, after 2 at index 1
+  fixed to 1,2,3:
+    move 1 to the front
+    move 2 after 1
+1 of 2 updates were out of order";
+        assert_eq!(expected, order.explain()?);
         Ok(())
     }
+
+    #[test]
+    fn should_solve_part2() -> Result<()> {
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(123), Solution::new().solve_part_two(&input)?);
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn should_parse_any_print_order(
+            rules in prop::collection::vec((10..100usize, 10..100usize), 1..30),
+            pages in prop::collection::vec(prop::collection::vec(10..100usize, 1..10), 1..10),
+        ) {
+            let text = format!(
+                "{}\n\n{}\n",
+                rules.iter().map(|(b, a)| format!("{b}|{a}")).join("\n"),
+                pages.iter().map(|p| p.iter().join(",")).join("\n")
+            );
+            let order = text.parse::<PrintOrder>().unwrap();
+            for (before, after) in rules {
+                prop_assert!(order.precedence[&after].contains(&before));
+            }
+            prop_assert_eq!(pages, order.pages);
+        }
+
+        #[test]
+        fn should_fix_by_moving(update in Just((10..30usize).collect_vec()).prop_shuffle()) {
+            let fixed = update.iter().copied().sorted().collect_vec();
+            prop_assert_eq!(&fixed, &apply(&update, &moves(&update, &fixed)));
+        }
+    }
 }
@@ -3,26 +3,45 @@ use std::{
     str::FromStr,
 };
 
-use crate::{common::IPoint, solver::Solver};
+use crate::{
+    answer::Answer,
+    common::{draw_grid, IPoint},
+    input::AdventInput,
+    solver::Solver,
+    visualize::{Frame, FrameSink, Rgb},
+};
 use anyhow::{anyhow, Result};
+use rand::prelude::*;
+use tracing::debug;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 06;
     const TITLE: &'static str = "Guard Gallivant";
+    type Input = LabMap;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let map = self.input().get_as::<LabMap>()?;
-        map.walk_guard()
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_as::<LabMap>()
+    }
+
+    fn part_one(&self, map: &Self::Input) -> Result<Answer> {
+        Ok(map.walk_guard()?.into())
+    }
+
+    fn part_two(&self, map: &Self::Input) -> Result<Answer> {
+        Ok(map.find_possible_obstructions()?.into())
+    }
+
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        Ok(generate(size, rng))
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let map = self.input().get_as::<LabMap>()?;
-        map.find_possible_obstructions()
+    fn visualize(&self, map: &Self::Input, sink: &mut dyn FrameSink) -> Result<()> {
+        map.visualize_walk(sink)
     }
 }
 
@@ -33,8 +52,9 @@ enum Tile {
 }
 
 const OFFSETS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const GUARD: [char; 4] = ['^', '>', 'v', '<'];
 
-struct LabMap {
+pub struct LabMap {
     tiles: HashMap<IPoint, Tile>,
     guard_start: IPoint,
 }
@@ -72,32 +92,83 @@ impl LabMap {
         let obstructions = find_possible_obstructions(&self.tiles, self.guard_start)?;
         Ok(obstructions.len())
     }
+
+    fn render(&self, path: &[IPoint], guard: (IPoint, usize)) -> Frame {
+        let width = self.tiles.keys().map(|p| p.x).max().unwrap_or(0) + 1;
+        let height = self.tiles.keys().map(|p| p.y).max().unwrap_or(0) + 1;
+        let mut frame = Frame::new(width as usize, height as usize);
+        for (p, t) in &self.tiles {
+            let glyph = match t {
+                Tile::Blocked => '#',
+                Tile::Clear => '.',
+            };
+            frame.set(p.x as usize, p.y as usize, glyph, None);
+        }
+        for p in path {
+            frame.set(p.x as usize, p.y as usize, 'X', Some(Rgb(80, 160, 255)));
+        }
+        let (pos, offset) = guard;
+        frame.set(
+            pos.x as usize,
+            pos.y as usize,
+            GUARD[offset],
+            Some(Rgb(255, 64, 64)),
+        );
+        frame
+    }
+
+    // One frame per turn, otherwise the real input is thousands of near-identical frames
+    fn visualize_walk(&self, sink: &mut dyn FrameSink) -> Result<()> {
+        let mut path = vec![];
+        let mut heading = 0;
+        let mut result = Ok(());
+        do_walk_with(&self.tiles, self.guard_start, |p, offset| {
+            if offset != heading && result.is_ok() {
+                result = sink.emit(&self.render(&path, (p, offset)));
+                heading = offset;
+            }
+            path.push(p);
+        })?;
+        result?;
+        let last = *path.last().unwrap_or(&self.guard_start);
+        sink.emit(&self.render(&path, (last, heading)))
+    }
 }
 
 fn do_walk(
     tiles: &HashMap<IPoint, Tile>,
     start: IPoint,
+) -> Result<HashMap<IPoint, HashSet<usize>>> {
+    do_walk_with(tiles, start, |_, _| ())
+}
+
+fn do_walk_with(
+    tiles: &HashMap<IPoint, Tile>,
+    start: IPoint,
+    mut on_step: impl FnMut(IPoint, usize),
 ) -> Result<HashMap<IPoint, HashSet<usize>>> {
     let mut visited = HashMap::new();
     let mut cur = start;
     let mut offset = 0;
     loop {
-        let next = cur + OFFSETS[offset];
         let offsets = visited.entry(cur).or_insert(HashSet::new());
         if !offsets.insert(offset) {
             return Err(anyhow!("Caught in a loop!"));
         }
-        if let Some(np) = tiles.get(&next) {
-            match np {
-                Tile::Blocked => {
-                    offset = (offset + 1) % 4;
-                    continue;
-                }
-                Tile::Clear => (),
+        // Only the heading we arrived with matters for loop detection, so turn in place
+        let mut next = cur + OFFSETS[offset];
+        for _ in 0..4 {
+            if tiles.get(&next) != Some(&Tile::Blocked) {
+                break;
             }
-            cur = next;
-        } else {
-            break;
+            offset = (offset + 1) % 4;
+            next = cur + OFFSETS[offset];
+        }
+        on_step(cur, offset);
+        match tiles.get(&next) {
+            Some(Tile::Clear) => cur = next,
+            Some(Tile::Blocked) => return Err(anyhow!("Boxed in!")),
+            None => break,
         }
     }
     Ok(visited)
@@ -114,12 +185,30 @@ fn find_possible_obstructions(tiles: &HashMap<IPoint, Tile>, start: IPoint) -> R
         let mut alt_tiles = tiles.clone();
         alt_tiles.entry(visited).and_modify(|t| *t = Tile::Blocked);
         if do_walk(&alt_tiles, start).is_err() {
+            debug!("Obstruction at {visited:?} traps the guard");
             obstructions.push(visited);
         }
     }
     Ok(obstructions)
 }
 
+// A `size` square lab with a sprinkling of obstacles
+fn generate(size: usize, rng: &mut StdRng) -> String {
+    let size = size.max(2);
+    loop {
+        let guard = (rng.random_range(0..size), rng.random_range(0..size));
+        let lab = draw_grid(size, size, |p| match (p.x, p.y) == guard {
+            true => '^',
+            false if rng.random_bool(0.08) => '#',
+            false => '.',
+        });
+        // The real inputs always let the guard walk out, so keep rolling until this one does
+        if lab.parse::<LabMap>().is_ok_and(|m| m.walk_guard().is_ok()) {
+            return lab;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,9 +261,8 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let map = EXAMPLE_INPUT.parse::<LabMap>()?;
-        let path = map.walk_guard()?;
-        assert_eq!(41, path);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(41), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
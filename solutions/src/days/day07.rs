@@ -1,26 +1,52 @@
-use std::str::FromStr;
-
-use crate::solver::Solver;
-use anyhow::{anyhow, Result};
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    answer::Answer,
+    input::{
+        parse::{self, ParseError},
+        validate, AdventInput,
+    },
+    solver::Solver,
+};
+use anyhow::Result;
 use itertools::Itertools;
+use rand::prelude::*;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 07;
     const TITLE: &'static str = "Bridge Repair";
+    type Input = Vec<Calibration>;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let cals = self.input().get_lines_as::<Calibration>()?;
-        Ok(valid_checksum(&cals, false))
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        Ok(parse::lines_with(&input.get()?, Calibration::parse)?)
+    }
+
+    fn validate(&self, cals: &Self::Input) -> Result<()> {
+        validate::each(cals, "Calibration", |c| {
+            validate::non_empty(&c.operands, "Operand list")?;
+            // Concatenating needs a number of digits, which zero doesn't have
+            for (i, &o) in c.operands.iter().enumerate() {
+                validate::in_range(o, 1..=usize::MAX, &format!("Operand {}", i + 1))?;
+            }
+            Ok(())
+        })
+    }
+
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        Ok(generate(size, rng))
+    }
+
+    fn part_one(&self, cals: &Self::Input) -> Result<Answer> {
+        Ok(valid_checksum(cals, false).into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let cals = self.input().get_lines_as::<Calibration>()?;
-        Ok(valid_checksum(&cals, true))
+    fn part_two(&self, cals: &Self::Input) -> Result<Answer> {
+        Ok(valid_checksum(cals, true).into())
     }
 }
 
@@ -33,7 +59,8 @@ const OPS_NO_CAT: [Operators; 2] = [Operators::Add, Operators::Mult];
 const OPS_WITH_CAT: [Operators; 3] = [Operators::Add, Operators::Mult, Operators::Cat];
 
 // Call Garrus, we've got some calibrations to do!
-struct Calibration {
+#[derive(Debug, PartialEq)]
+pub struct Calibration {
     result: usize,
     operands: Vec<usize>,
 }
@@ -41,15 +68,24 @@ impl FromStr for Calibration {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let [left, right] = s.split(": ").collect_vec()[..2] else {
-            return Err(anyhow!("Invalid calibration"));
-        };
-        let result = left.parse()?;
-        let operands = right.split(' ').filter_map(|x| x.parse().ok()).collect();
-        Ok(Self { result, operands })
+        Ok(Self::parse(s)?)
+    }
+}
+impl Display for Calibration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.result)?;
+        for o in &self.operands {
+            write!(f, " {o}")?;
+        }
+        Ok(())
     }
 }
 impl Calibration {
+    fn parse(s: &str) -> Result<Self, ParseError> {
+        let (result, operands) = parse::key_list(s, ":")?;
+        Ok(Self { result, operands })
+    }
+
     fn is_computable(&self, concat: bool) -> bool {
         let ops = if concat {
             &OPS_WITH_CAT[..]
@@ -84,9 +120,40 @@ fn valid_checksum(cals: &[Calibration], concat: bool) -> usize {
         .sum()
 }
 
+// Most real calibrations can't be made to work, so only about half of these are built from actual operators
+fn generate(size: usize, rng: &mut StdRng) -> String {
+    const LIMIT: usize = 1_000_000_000_000_000;
+    (0..size)
+        .map(|_| {
+            let count = rng.random_range(2..=12);
+            let operands = (0..count)
+                .map(|_| rng.random_range(1..1000usize))
+                .collect_vec();
+            let mut result = operands[0];
+            for &next in &operands[1..] {
+                let possibility = match rng.random_range(0..3) {
+                    0 => result.checked_mul(next),
+                    1 => 10usize
+                        .checked_pow(next.ilog10() + 1)
+                        .and_then(|shift| result.checked_mul(shift))
+                        .map(|r| r + next),
+                    _ => None,
+                };
+                // Fall back to adding so the numbers stay sensible
+                result = possibility.filter(|&r| r < LIMIT).unwrap_or(result + next);
+            }
+            if rng.random_bool(0.5) {
+                result += rng.random_range(1..10);
+            }
+            Calibration { result, operands }.to_string()
+        })
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "190: 10 19
 3267: 81 40 27
@@ -107,6 +174,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_report_bad_line() {
+        let input = AdventInput::from_text("190: 10 19\n3267: 81 x 27\n83: 17 5");
+        let Err(e) = Solution::new().load(&input) else {
+            panic!("Parsed a calibration with an x in it");
+        };
+        assert_eq!("line 2, column 10: 'x' isn't a valid usize", e.to_string());
+    }
+
+    #[test]
+    fn should_reject_zero_operand() {
+        let input = AdventInput::from_text("190: 10 19\n12: 1 0");
+        let Err(e) = Solution::new().load(&input) else {
+            panic!("Loaded a calibration with a zero operand");
+        };
+        assert_eq!(
+            format!(
+                "Calibration 2 is invalid: Operand 2 is 0, but should be between 1 and {}",
+                usize::MAX
+            ),
+            format!("{e:#}")
+        );
+    }
+
     #[test]
     fn should_be_computable() -> Result<()> {
         let cal = "24: 2 3 4".parse::<Calibration>()?;
@@ -123,12 +214,8 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let cals = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Calibration>().ok())
-            .collect_vec();
-        let checksum = valid_checksum(&cals, false);
-        assert_eq!(3749, checksum);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(3749), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -141,12 +228,16 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let cals = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Calibration>().ok())
-            .collect_vec();
-        let checksum = valid_checksum(&cals, true);
-        assert_eq!(11387, checksum);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(11387), Solution::new().solve_part_two(&input)?);
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn should_round_trip(result: usize, operands in prop::collection::vec(any::<usize>(), 1..12)) {
+            let cal = Calibration { result, operands };
+            prop_assert_eq!(&cal, &cal.to_string().parse::<Calibration>().unwrap());
+        }
+    }
 }
@@ -3,32 +3,45 @@ use std::{
     str::FromStr,
 };
 
-use crate::{common::IPoint, solver::Solver};
+use crate::{
+    answer::Answer,
+    common::{draw_grid, IPoint},
+    input::AdventInput,
+    solver::Solver,
+};
 use anyhow::Result;
+use rand::prelude::*;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 08;
     const TITLE: &'static str = "Resonant Collinearity";
+    type Input = Map;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let map = self.input().get_as::<Map>()?;
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_as::<Map>()
+    }
+
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        Ok(generate(size, rng))
+    }
+
+    fn part_one(&self, map: &Self::Input) -> Result<Answer> {
         let antinodes = map.find_antinodes();
-        Ok(antinodes.len())
+        Ok(antinodes.len().into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let map = self.input().get_as::<Map>()?;
+    fn part_two(&self, map: &Self::Input) -> Result<Answer> {
         let antinodes = map.find_resonant_antinodes();
-        Ok(antinodes.len())
+        Ok(antinodes.len().into())
     }
 }
 
-struct Map {
+pub struct Map {
     antennas: HashMap<char, Vec<IPoint>>,
     max_x: isize,
     max_y: isize,
@@ -115,6 +128,15 @@ impl Map {
     }
 }
 
+// A `size` square map, sparse enough that each frequency only has a handful of antennas
+fn generate(size: usize, rng: &mut StdRng) -> String {
+    const FREQUENCIES: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    draw_grid(size.max(1), size.max(1), |_| match rng.random_bool(0.04) {
+        true => *FREQUENCIES.choose(rng).unwrap() as char,
+        false => '.',
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,9 +183,8 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let map = EXAMPLE_INPUT.parse::<Map>()?;
-        let antinodes = map.find_antinodes();
-        assert_eq!(14, antinodes.len());
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(14), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -188,9 +209,8 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let map = EXAMPLE_INPUT.parse::<Map>()?;
-        let antinodes = map.find_resonant_antinodes();
-        assert_eq!(34, antinodes.len());
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(34), Solution::new().solve_part_two(&input)?);
         Ok(())
     }
 }
@@ -1,30 +1,87 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::solver::Solver;
-use anyhow::Result;
+use crate::{
+    answer::Answer,
+    crosscheck::CrossCheck,
+    input::AdventInput,
+    solver::Solver,
+    visualize::{Frame, FrameSink, Rgb},
+};
+use anyhow::{anyhow, Result};
+use rand::prelude::*;
+use tracing::trace;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 09;
     const TITLE: &'static str = "Disk Fragmenter";
+    type Input = Filesystem;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let mut fs = self.input().get_as::<Filesystem>()?;
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_as::<Filesystem>()
+    }
+
+    fn validate(&self, fs: &Self::Input) -> Result<()> {
+        // Compacting needs at least one file block to work towards
+        match fs.blocks.iter().any(|b| matches!(b, Block::File(_))) {
+            true => Ok(()),
+            false => Err(anyhow!("Disk map has no files in it")),
+        }
+    }
+
+    fn part_one(&self, fs: &Self::Input) -> Result<Answer> {
+        let mut fs = fs.clone();
         fs.compact();
-        Ok(fs.checksum())
+        Ok(fs.checksum().into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let mut fs = self.input().get_as::<Filesystem>()?;
+    fn part_two(&self, fs: &Self::Input) -> Result<Answer> {
+        let mut fs = fs.clone();
         fs.smarter_compact();
-        Ok(fs.checksum())
+        Ok(fs.checksum().into())
+    }
+
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        generate(size, rng)
+    }
+
+    fn crosschecks(&self) -> Vec<CrossCheck<Self::Input>> {
+        vec![CrossCheck {
+            name: "whole file compaction",
+            fast: |fs| {
+                let mut fs = fs.clone();
+                fs.smarter_compact();
+                fs.checksum().into()
+            },
+            reference: |fs| {
+                let mut fs = fs.clone();
+                fs.naive_smarter_compact();
+                fs.checksum().into()
+            },
+        }]
+    }
+
+    fn visualize(&self, fs: &Self::Input, sink: &mut dyn FrameSink) -> Result<()> {
+        let mut fs = fs.clone();
+        // Aim for ~100 frames no matter how many files there are
+        let every = (fs.max_id as usize / 100).max(1);
+        let mut result = sink.emit(&fs.render());
+        fs.smarter_compact_with(|fs, id| {
+            if (id as usize).is_multiple_of(every) && result.is_ok() {
+                result = sink.emit(&fs.render());
+            }
+        });
+        result?;
+        sink.emit(&fs.render())
     }
 }
 
+const FRAME_WIDTH: usize = 100;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Block {
     Free,
@@ -37,7 +94,8 @@ struct File {
     start: usize,
 }
 
-struct Filesystem {
+#[derive(Clone)]
+pub struct Filesystem {
     // let's expand the compact, efficient description into a bloated huge one
     blocks: Vec<Block>,
     file_map: HashMap<u16, File>,
@@ -52,7 +110,7 @@ impl FromStr for Filesystem {
         let mut file = true;
         let mut id = 0;
         for c in s.chars() {
-            if !c.is_digit(10) {
+            if !c.is_ascii_digit() {
                 continue;
             }
             let len = c.to_digit(10).unwrap();
@@ -70,14 +128,19 @@ impl FromStr for Filesystem {
                         start: blocks.len() - len as usize,
                     },
                 );
-                id += 1;
+                id = id
+                    .checked_add(1)
+                    .ok_or(anyhow!("Too many files for a u16 id"))?;
             }
             file = !file;
         }
+        let Some(max_id) = id.checked_sub(1) else {
+            return Err(anyhow!("Disk map has no files in it"));
+        };
         Ok(Self {
             blocks,
             file_map,
-            max_id: id - 1,
+            max_id,
         })
     }
 }
@@ -93,12 +156,16 @@ impl Filesystem {
     }
 
     fn compact(&mut self) {
-        let (mut i, mut j) = (0, self.blocks.len() - 1);
+        let Some(mut j) = self.blocks.len().checked_sub(1) else {
+            return;
+        };
+        let mut i = 0;
+        // Stop when they meet, otherwise a disk with no free space runs off the end
         loop {
-            while self.blocks[i] != Block::Free {
+            while i < j && self.blocks[i] != Block::Free {
                 i += 1;
             }
-            while self.blocks[j] == Block::Free {
+            while j > i && self.blocks[j] == Block::Free {
                 j -= 1;
             }
             if i >= j {
@@ -131,7 +198,51 @@ impl Filesystem {
         }
     }
 
+    fn render(&self) -> Frame {
+        let mut frame = Frame::new(FRAME_WIDTH, self.blocks.len().div_ceil(FRAME_WIDTH));
+        for (i, b) in self.blocks.iter().enumerate() {
+            let (glyph, color) = match b {
+                Block::Free => ('.', None),
+                Block::File(id) => ('#', Some(Rgb::from_id(*id as usize))),
+            };
+            frame.set(i % FRAME_WIDTH, i / FRAME_WIDTH, glyph, color);
+        }
+        frame
+    }
+
+    // No file map, no cleverness: find each file, then scan from the left for the first gap that fits it
+    fn naive_smarter_compact(&mut self) {
+        for id in (1..=self.max_id).rev() {
+            let Some(start) = self.blocks.iter().position(|&b| b == Block::File(id)) else {
+                continue;
+            };
+            let len = self.blocks[start..]
+                .iter()
+                .take_while(|&&b| b == Block::File(id))
+                .count();
+            let mut gap = 0;
+            for i in 0..start {
+                gap = match self.blocks[i] {
+                    Block::Free => gap + 1,
+                    Block::File(_) => 0,
+                };
+                if gap == len {
+                    let to = i + 1 - len;
+                    for k in 0..len {
+                        self.blocks[to + k] = Block::File(id);
+                        self.blocks[start + k] = Block::Free;
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
     fn smarter_compact(&mut self) {
+        self.smarter_compact_with(|_, _| ());
+    }
+
+    fn smarter_compact_with(&mut self, mut on_move: impl FnMut(&Self, u16)) {
         for id in (1..=self.max_id).rev() {
             let file = self.file_map.get(&id).unwrap();
             let Some(free_start) = self.find_free_space(file.blocks, file.start) else {
@@ -146,13 +257,32 @@ impl Filesystem {
                 self.blocks[old] = Block::Free;
             }
             self.file_map.entry(id).and_modify(|e| e.start = free_start);
+            trace!("Moved file {id} to block {free_start}");
+            on_move(self, id);
+        }
+    }
+}
+
+// A disk map with `size` files, which is as many as the ids allow at most
+fn generate(size: usize, rng: &mut StdRng) -> Result<String> {
+    if size > u16::MAX as usize {
+        return Err(anyhow!("Can't have more than {} files", u16::MAX));
+    }
+    let mut map = String::with_capacity(size * 2);
+    for i in 0..size.max(1) {
+        if i > 0 {
+            map.push(char::from(b'0' + rng.random_range(0..10)));
         }
+        map.push(char::from(b'0' + rng.random_range(1..10)));
     }
+    Ok(map)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crosscheck;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "2333133121414131402";
 
@@ -211,9 +341,8 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let mut fs = EXAMPLE_INPUT.parse::<Filesystem>()?;
-        fs.compact();
-        assert_eq!(1928, fs.checksum());
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(1928), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -235,9 +364,56 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let mut fs = EXAMPLE_INPUT.parse::<Filesystem>()?;
-        fs.smarter_compact();
-        assert_eq!(2858, fs.checksum());
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(2858), Solution::new().solve_part_two(&input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_empty_disk() {
+        let input = AdventInput::from_text("0");
+        assert!(Solution::new().solve_part_one(&input).is_err());
+    }
+
+    #[test]
+    fn should_leave_full_disk_alone() -> Result<()> {
+        for (map, checksum) in [("1", 0), ("101", 1), ("90", 0)] {
+            let input = AdventInput::from_text(map);
+            assert_eq!(
+                Answer::from(checksum),
+                Solution::new().solve_part_one(&input)?
+            );
+            assert_eq!(
+                Answer::from(checksum),
+                Solution::new().solve_part_two(&input)?
+            );
+        }
         Ok(())
     }
+
+    #[test]
+    fn should_agree_with_reference() {
+        crosscheck::assert_agrees(&Solution::new(), EXAMPLE_INPUT);
+    }
+
+    proptest! {
+        #[test]
+        fn should_agree_on_generated_input(seed: u64) {
+            let map = generate(12, &mut StdRng::seed_from_u64(seed)).unwrap();
+            crosscheck::assert_agrees(&Solution::new(), &map);
+        }
+
+        #[test]
+        fn should_agree_with_reference_on_anything(map in "[1-9][0-9]{0,40}") {
+            crosscheck::assert_agrees(&Solution::new(), &map);
+        }
+
+        #[test]
+        fn should_parse_any_disk_map(map in "[1-9][0-9]{0,40}") {
+            let fs = map.parse::<Filesystem>().unwrap();
+            let size: u32 = map.chars().filter_map(|c| c.to_digit(10)).sum();
+            prop_assert_eq!(size as usize, fs.blocks.len());
+            prop_assert_eq!(map.len().div_ceil(2) - 1, fs.max_id as usize);
+        }
+    }
 }
@@ -3,32 +3,77 @@ use std::{
     str::FromStr,
 };
 
-use crate::{common::IPoint, solver::Solver};
+use crate::{
+    answer::Answer,
+    common::{draw_grid, IPoint},
+    crosscheck::CrossCheck,
+    input::{validate, AdventInput},
+    solver::Solver,
+    visualize::{Frame, FrameSink, Rgb},
+};
 use anyhow::Result;
+use rand::prelude::*;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 10;
     const TITLE: &'static str = "Hoof It";
+    type Input = TrailMap;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let map = self.input().get_as::<TrailMap>()?;
-        Ok(map.get_hiking_score())
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_as::<TrailMap>()
+    }
+
+    fn part_one(&self, map: &Self::Input) -> Result<Answer> {
+        Ok(map.get_hiking_score().into())
+    }
+
+    fn part_two(&self, map: &Self::Input) -> Result<Answer> {
+        Ok(map.get_rating().into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let map = self.input().get_as::<TrailMap>()?;
-        Ok(map.get_rating())
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        Ok(generate(size, rng))
+    }
+
+    fn crosschecks(&self) -> Vec<CrossCheck<Self::Input>> {
+        vec![
+            CrossCheck {
+                name: "score",
+                fast: |map| map.get_hiking_score().into(),
+                reference: |map| {
+                    let peaks = map.trailheads.iter().map(|th| map.reachable_peaks(th));
+                    peaks.sum::<usize>().into()
+                },
+            },
+            CrossCheck {
+                name: "rating",
+                fast: |map| map.get_rating().into(),
+                reference: |map| {
+                    let trails = map.trailheads.iter().map(|th| map.walk_every_trail(th, 0));
+                    trails.sum::<usize>().into()
+                },
+            },
+        ]
+    }
+
+    fn visualize(&self, map: &Self::Input, sink: &mut dyn FrameSink) -> Result<()> {
+        for th in &map.trailheads {
+            let mut endpoints = HashSet::new();
+            map.find_endpoints(th, &0, &mut endpoints);
+            sink.emit(&map.render(th, &endpoints))?;
+        }
+        Ok(())
     }
 }
 
 const OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
 
-struct TrailMap {
+pub struct TrailMap {
     tiles: HashMap<IPoint, u8>,
     trailheads: Vec<IPoint>,
 }
@@ -36,12 +81,13 @@ impl FromStr for TrailMap {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        validate::rectangular(s)?;
         let mut tiles = HashMap::new();
         let mut trailheads = vec![];
         for (y, l) in s.lines().enumerate() {
             let y = y as isize;
             for (x, c) in l.chars().enumerate() {
-                if !c.is_digit(10) {
+                if !c.is_ascii_digit() {
                     continue;
                 }
                 let x = x as isize;
@@ -84,6 +130,51 @@ impl TrailMap {
         }
     }
 
+    fn render(&self, trailhead: &IPoint, endpoints: &HashSet<IPoint>) -> Frame {
+        let width = self.tiles.keys().map(|p| p.x).max().unwrap_or(0) + 1;
+        let height = self.tiles.keys().map(|p| p.y).max().unwrap_or(0) + 1;
+        let mut frame = Frame::new(width as usize, height as usize);
+        for (p, &v) in &self.tiles {
+            let shade = 40 + v * 20;
+            let color = if p == trailhead {
+                Rgb(64, 255, 64)
+            } else if endpoints.contains(p) {
+                Rgb(255, 64, 64)
+            } else {
+                Rgb(shade, shade, shade)
+            };
+            let glyph = char::from_digit(v.into(), 10).unwrap_or('?');
+            frame.set(p.x as usize, p.y as usize, glyph, Some(color));
+        }
+        frame
+    }
+
+    // Spread out one height at a time and see how many 9s are left standing
+    fn reachable_peaks(&self, trailhead: &IPoint) -> usize {
+        let mut frontier = HashSet::from([*trailhead]);
+        for height in 1..=9 {
+            frontier = frontier
+                .iter()
+                .flat_map(|p| OFFSETS.iter().map(move |o| p + o))
+                .filter(|p| self.tiles.get(p) == Some(&height))
+                .collect();
+        }
+        frontier.len()
+    }
+
+    // Every trail, one at a time, no memoizing
+    fn walk_every_trail(&self, start: &IPoint, value: u8) -> usize {
+        if value == 9 {
+            return 1;
+        }
+        OFFSETS
+            .iter()
+            .map(|o| start + o)
+            .filter(|p| self.tiles.get(p) == Some(&(value + 1)))
+            .map(|p| self.walk_every_trail(&p, value + 1))
+            .sum()
+    }
+
     fn get_rating(&self) -> usize {
         let mut rating = 0;
         let mut visited = HashMap::new();
@@ -124,9 +215,19 @@ impl TrailMap {
     }
 }
 
+// Random digits hardly ever line up into a trail, so this is a set of ramps with a bit of noise on top
+fn generate(size: usize, rng: &mut StdRng) -> String {
+    draw_grid(size.max(1), size.max(1), |p| {
+        let height = (p.x + p.y + rng.random_range(0..2)) % 10;
+        char::from(b'0' + height as u8)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crosscheck;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "89010123
 78121874
@@ -165,9 +266,8 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let map = EXAMPLE_INPUT.parse::<TrailMap>()?;
-        let score = map.get_hiking_score();
-        assert_eq!(36, score);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(36), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -181,9 +281,32 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let map = EXAMPLE_INPUT.parse::<TrailMap>()?;
-        let score = map.get_rating();
-        assert_eq!(81, score);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(81), Solution::new().solve_part_two(&input)?);
         Ok(())
     }
+
+    #[test]
+    fn should_reject_ragged_maps() {
+        let input = AdventInput::from_text("0123\n123\n8765");
+        assert!(Solution::new().solve_part_one(&input).is_err());
+    }
+
+    #[test]
+    fn should_agree_with_reference() {
+        crosscheck::assert_agrees(&Solution::new(), EXAMPLE_INPUT);
+    }
+
+    proptest! {
+        #[test]
+        fn should_agree_on_generated_input(seed: u64) {
+            let map = generate(12, &mut StdRng::seed_from_u64(seed));
+            crosscheck::assert_agrees(&Solution::new(), &map);
+        }
+
+        #[test]
+        fn should_agree_with_reference_on_anything(rows in prop::collection::vec("[0-9]{8}", 1..8)) {
+            crosscheck::assert_agrees(&Solution::new(), &rows.join("\n"));
+        }
+    }
 }
@@ -1,28 +1,45 @@
 use std::collections::HashMap;
 
-use crate::solver::Solver;
+use crate::{answer::Answer, crosscheck::CrossCheck, input::AdventInput, solver::Solver};
 use anyhow::Result;
+use tracing::trace;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 11;
     const TITLE: &'static str = "Plutonian Pebbles";
+    type Input = Vec<usize>;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let mut stones = self.input().get_split_as::<usize>(' ')?;
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_ints::<usize>()
+    }
+
+    fn part_one(&self, stones: &Self::Input) -> Result<Answer> {
+        let mut stones = stones.clone();
         for _ in 0..25 {
             stones = blink(&stones);
         }
-        Ok(stones.len())
+        Ok(stones.len().into())
+    }
+
+    fn part_two(&self, stones: &Self::Input) -> Result<Answer> {
+        Ok(blink_smarter_not_harder(stones, 75).into())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let stones = self.input().get_split_as::<usize>(' ')?;
-        Ok(blink_smarter_not_harder(&stones, 75))
+    fn crosschecks(&self) -> Vec<CrossCheck<Self::Input>> {
+        // 75 blinks of the real thing would never finish, but 25 is plenty to catch a slip
+        vec![CrossCheck {
+            name: "25 blinks",
+            fast: |stones| blink_smarter_not_harder(stones, 25).into(),
+            reference: |stones| {
+                let stones = (0..25).fold(stones.clone(), |s, _| blink(&s));
+                stones.len().into()
+            },
+        }]
     }
 }
 
@@ -32,7 +49,7 @@ fn blink(stones: &[usize]) -> Vec<usize> {
         if s == 0 {
             output.push(1);
         } else if (s.ilog10() + 1) % 2 == 0 {
-            let split = 10usize.pow((s.ilog10() + 1) / 2);
+            let split = 10usize.pow(s.ilog10().div_ceil(2));
             let left = s / split;
             let right = s - (left * split);
             output.push(left);
@@ -48,12 +65,13 @@ fn blink_smarter_not_harder(stones: &[usize], iterations: usize) -> usize {
     let mut stone_counts: HashMap<usize, usize> =
         HashMap::from_iter(stones.iter().map(|&s| (s, 1usize)));
     let mut new_counts: HashMap<usize, usize> = HashMap::new();
-    for _ in 0..iterations {
+    for i in 0..iterations {
+        trace!("{} distinct stones after {i} blinks", stone_counts.len());
         for (&k, &num) in &stone_counts {
             if k == 0 {
                 *new_counts.entry(1).or_insert(0) += num;
             } else if (k.ilog10() + 1) % 2 == 0 {
-                let split = 10usize.pow((k.ilog10() + 1) / 2);
+                let split = 10usize.pow(k.ilog10().div_ceil(2));
                 let left = k / split;
                 let right = k - (left * split);
                 *new_counts.entry(left).or_insert(0) += num;
@@ -72,6 +90,9 @@ fn blink_smarter_not_harder(stones: &[usize], iterations: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crosscheck;
+    use itertools::Itertools;
+    use proptest::prelude::*;
 
     #[test]
     fn should_blink() {
@@ -82,12 +103,10 @@ mod tests {
     }
 
     #[test]
-    fn should_solve_part1() {
-        let mut test = vec![125, 17];
-        for _ in 0..25 {
-            test = blink(&test);
-        }
-        assert_eq!(55312, test.len());
+    fn should_solve_part1() -> Result<()> {
+        let input = AdventInput::from_text("125 17");
+        assert_eq!(Answer::from(55312), Solution::new().solve_part_one(&input)?);
+        Ok(())
     }
 
     #[test]
@@ -96,4 +115,19 @@ mod tests {
         let stone_count = blink_smarter_not_harder(&test, 25);
         assert_eq!(55312, stone_count);
     }
+
+    #[test]
+    fn should_agree_with_brute_force() {
+        crosscheck::assert_agrees(&Solution::new(), "125 17");
+    }
+
+    proptest! {
+        // Brute force is slow enough that a handful of cases will do
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn should_agree_with_brute_force_on_anything(stones in prop::collection::vec(0..100_000usize, 1..4)) {
+            crosscheck::assert_agrees(&Solution::new(), &stones.iter().join(" "));
+        }
+    }
 }
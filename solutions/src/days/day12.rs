@@ -3,25 +3,65 @@ use std::{
     str::FromStr,
 };
 
-use crate::{common::IPoint, solver::Solver};
+use crate::{
+    answer::Answer,
+    common::{draw_grid, IPoint},
+    input::{validate, AdventInput},
+    solver::Solver,
+    visualize::{Frame, FrameSink, Rgb},
+};
 use anyhow::Result;
+use itertools::Itertools;
+use rand::prelude::*;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 12;
     const TITLE: &'static str = "Garden Groups";
+    type Input = Garden;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let garden = self.input().get_as::<Garden>()?;
-        Ok(garden.get_fence_cost())
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        input.get_as::<Garden>()
+    }
+
+    fn part_one(&self, garden: &Self::Input) -> Result<Answer> {
+        Ok(garden.get_fence_cost().into())
+    }
+
+    fn part_two(&self, _input: &Self::Input) -> Result<Answer> {
+        Ok(0.into())
+    }
+
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        Ok(generate(size, rng))
     }
 
-    fn part_two(&self) -> Result<usize> {
-        Ok(0)
+    fn visualize(&self, garden: &Self::Input, sink: &mut dyn FrameSink) -> Result<()> {
+        let regions = garden.get_regions();
+        // Colour in the regions as they're found; one frame per region is too slow on the real input
+        let every = (regions.len() / 100).max(1);
+        let mut frame = Frame::new(garden.max_x as usize + 1, garden.max_y as usize + 1);
+        for (p, &c) in &garden.tiles {
+            frame.set(p.x as usize, p.y as usize, c, None);
+        }
+        for (i, r) in regions.iter().enumerate() {
+            for p in &r.points {
+                frame.set(
+                    p.x as usize,
+                    p.y as usize,
+                    r.identifier,
+                    Some(Rgb::from_id(i)),
+                );
+            }
+            if i.is_multiple_of(every) {
+                sink.emit(&frame)?;
+            }
+        }
+        sink.emit(&frame)
     }
 }
 
@@ -42,7 +82,7 @@ impl Region {
     }
 }
 
-struct Garden {
+pub struct Garden {
     tiles: HashMap<IPoint, char>,
     max_x: isize,
     max_y: isize,
@@ -51,6 +91,7 @@ impl FromStr for Garden {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        validate::rectangular(s)?;
         let mut tiles = HashMap::new();
         let (mut max_x, mut max_y) = (0, 0);
         for (y, l) in s.lines().enumerate() {
@@ -119,6 +160,20 @@ impl Garden {
     }
 }
 
+// Patches of a few plants each, so neighbouring patches merge into oddly shaped regions, plus the odd stray plant
+fn generate(size: usize, rng: &mut StdRng) -> String {
+    const PATCH: usize = 4;
+    let size = size.max(1);
+    let patches = size.div_ceil(PATCH);
+    let plants = (0..patches * patches)
+        .map(|_| char::from(rng.random_range(b'A'..=b'F')))
+        .collect_vec();
+    draw_grid(size, size, |p| match rng.random_bool(0.05) {
+        true => char::from(rng.random_range(b'A'..=b'Z')),
+        false => plants[(p.y / PATCH) * patches + p.x / PATCH],
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::anyhow;
@@ -180,8 +235,8 @@ MMMISSJEEE";
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let garden = EXAMPLE_INPUT.parse::<Garden>()?;
-        assert_eq!(1930, garden.get_fence_cost());
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(1930), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 }
@@ -1,44 +1,65 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
-use crate::{common::IPoint, solver::Solver};
+use crate::{
+    answer::Answer,
+    common::IPoint,
+    input::{
+        parse::{self, Section},
+        AdventInput,
+    },
+    solver::Solver,
+};
 use anyhow::{anyhow, Result};
-use lazy_static::lazy_static;
-use regex::Regex;
+use itertools::Itertools;
+use rand::prelude::*;
+use tracing::debug;
 
 pub struct Solution;
-impl Solver<isize, isize> for Solution {
+impl Solver for Solution {
     const DAY: u8 = 13;
     const TITLE: &'static str = "UNKNOWN";
+    type Input = Vec<ClawMachine>;
 
     fn new() -> Self {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<isize> {
-        let machines = self.input().get_grouped_as::<ClawMachine>()?;
-        Ok(machines.iter().filter_map(|m| m.cost_to_win(0)).sum())
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input> {
+        parse::sections(&input.get()?)
+            .iter()
+            .map(ClawMachine::from_section)
+            .collect()
+    }
+
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Result<String> {
+        Ok(generate(size, rng))
+    }
+
+    fn part_one(&self, machines: &Self::Input) -> Result<Answer> {
+        Ok(machines
+            .iter()
+            .filter_map(|m| m.cost_to_win(0))
+            .sum::<isize>()
+            .into())
     }
 
-    fn part_two(&self) -> Result<isize> {
-        let machines = self.input().get_grouped_as::<ClawMachine>()?;
+    fn part_two(&self, machines: &Self::Input) -> Result<Answer> {
         Ok(machines
             .iter()
             .filter_map(|m| m.cost_to_win(PRIZE_OFFSET))
-            .sum())
+            .sum::<isize>()
+            .into())
     }
 }
 
 type Vector = IPoint;
 
-lazy_static! {
-    static ref BUTTONS: Regex = Regex::new(r"Button (A|B): X\+(\d+), Y\+(\d+)").unwrap();
-    static ref PRIZES: Regex = Regex::new(r"Prize: X=(\d+), Y=(\d+)").unwrap();
-}
 const A_COST: isize = 3;
 const B_COST: isize = 1;
 const PRIZE_OFFSET: isize = 10_000_000_000_000;
 
-struct ClawMachine {
+#[derive(Debug, PartialEq)]
+pub struct ClawMachine {
     prize: IPoint,
     a_button: Vector,
     b_button: Vector,
@@ -47,27 +68,36 @@ impl FromStr for ClawMachine {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (mut a_button, mut b_button) = (Vector::new(0, 0), Vector::new(0, 0));
-        for cap in BUTTONS.captures_iter(s) {
-            let v = Vector::new(cap[2].parse()?, cap[3].parse()?);
-            match &cap[1] {
-                "A" => a_button = v,
-                "B" => b_button = v,
-                _ => return Err(anyhow!("Invalid button specification")),
-            }
-        }
-        let Some(cap) = PRIZES.captures(s) else {
-            return Err(anyhow!("Invalid prize specification"));
+        Self::from_section(&Section { line: 0, text: s })
+    }
+}
+impl Display for ClawMachine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (a, b, p) = (self.a_button, self.b_button, self.prize);
+        writeln!(f, "Button A: X+{}, Y+{}", a.x, a.y)?;
+        writeln!(f, "Button B: X+{}, Y+{}", b.x, b.y)?;
+        write!(f, "Prize: X={}, Y={}", p.x, p.y)
+    }
+}
+impl ClawMachine {
+    fn from_section(section: &Section) -> Result<Self> {
+        // Button A, then button B, then the prize, each with an X and a Y
+        let xys = section.lines_with(|l| parse::labeled(l, &["X", "Y"]))?;
+        let [a_button, b_button, prize] =
+            xys.iter().map(|xy| Vector::new(xy[0], xy[1])).collect_vec()[..]
+        else {
+            return Err(anyhow!(
+                "Invalid claw machine specification at line {}",
+                section.line + 1
+            ));
         };
-        let prize = IPoint::new(cap[1].parse()?, cap[2].parse()?);
         Ok(Self {
             a_button,
             b_button,
             prize,
         })
     }
-}
-impl ClawMachine {
+
     fn get_pushes_to_prize(&self, offset: isize) -> Option<(isize, isize)> {
         // jfc i can't believe this stupid problem has me watching MATH VIDEOS ON YOUTUBE LIKE I'M IN HIGH SCHOOL OR SOMETHING
         // When I first read this one I saw "minimum button presses" and thought it was another LCM puzzle.
@@ -75,26 +105,120 @@ impl ClawMachine {
         // Oh wait.
         let prize = self.prize + (offset, offset);
         let z = self.a_button.x * self.b_button.y - self.a_button.y * self.b_button.x;
+        if z == 0 {
+            return self.get_pushes_along_line(prize);
+        }
         let a = prize.x * self.b_button.y - prize.y * self.b_button.x;
         let b = prize.y * self.a_button.x - prize.x * self.a_button.y;
-        if a % z == 0 && b % z == 0 {
+        // Buttons can't be pushed a negative number of times, same as along a line
+        if a % z == 0 && b % z == 0 && a / z >= 0 && b / z >= 0 {
             Some((a / z, b / z))
         } else {
+            debug!("Prize at {prize:?} can't be won");
             None
         }
     }
 
-    fn cost_to_win(&self, offset: isize) -> Option<isize> {
-        let Some((a, b)) = self.get_pushes_to_prize(offset) else {
+    // Both buttons push the claw the same way, so there's no single answer to find.
+    // The prize has to be on their line, and then it's the cheapest mix of pushes along it.
+    fn get_pushes_along_line(&self, prize: IPoint) -> Option<(isize, isize)> {
+        let (a, b) = (self.a_button, self.b_button);
+        if a.x * prize.y != a.y * prize.x || b.x * prize.y != b.y * prize.x {
+            debug!("Prize at {prize:?} is off the buttons' line");
             return None;
-        };
+        }
+        // Either coordinate pins down where on the line we are, as long as the buttons move it
+        if a.x != 0 || b.x != 0 {
+            cheapest_pushes(a.x, b.x, prize.x)
+        } else if a.y != 0 || b.y != 0 {
+            cheapest_pushes(a.y, b.y, prize.y)
+        } else {
+            (prize == IPoint::new(0, 0)).then_some((0, 0))
+        }
+    }
+
+    fn cost_to_win(&self, offset: isize) -> Option<isize> {
+        let (a, b) = self.get_pushes_to_prize(offset)?;
         Some(a * A_COST + b * B_COST)
     }
 }
 
+// Non-negative `a` and `b` with `a * u + b * v == w` that cost the least
+fn cheapest_pushes(u: isize, v: isize, w: isize) -> Option<(isize, isize)> {
+    let (g, x, y) = extended_gcd(u, v);
+    if w % g != 0 {
+        return None;
+    }
+    // Every answer is (a0 + k * da, b0 + k * db) for some k
+    let (a0, b0) = (x * (w / g), y * (w / g));
+    let (da, db) = (v / g, -u / g);
+    let (mut lo, mut hi) = (isize::MIN, isize::MAX);
+    for (start, step) in [(a0, da), (b0, db)] {
+        match step.signum() {
+            1 => lo = lo.max(-start.div_euclid(step)),
+            -1 => hi = hi.min(start.div_euclid(-step)),
+            _ if start < 0 => return None,
+            _ => (),
+        }
+    }
+    if lo > hi {
+        return None;
+    }
+    // The cost goes up or down steadily with k, so the cheapest is at one end
+    let k = match A_COST * da + B_COST * db >= 0 {
+        true => lo,
+        false => hi,
+    };
+    Some((a0 + k * da, b0 + k * db))
+}
+
+// (g, x, y) with a * x + b * y == g, and g positive
+fn extended_gcd(a: isize, b: isize) -> (isize, isize, isize) {
+    if b == 0 {
+        return (a.abs(), a.signum(), 0);
+    }
+    let (g, x, y) = extended_gcd(b, a % b);
+    (g, y, x - (a / b) * y)
+}
+
+// `size` machines, about half of which can actually be won
+fn generate(size: usize, rng: &mut StdRng) -> String {
+    // rand doesn't do isize
+    let number = |rng: &mut StdRng, range: std::ops::Range<i64>| rng.random_range(range) as isize;
+    (0..size)
+        .map(|_| {
+            let (a_button, b_button) = loop {
+                let a = Vector::new(number(rng, 10..100), number(rng, 10..100));
+                let b = Vector::new(number(rng, 10..100), number(rng, 10..100));
+                // Buttons that push the claw the same way don't have a single answer, and the real inputs never have them
+                if a.x * b.y != a.y * b.x {
+                    break (a, b);
+                }
+            };
+            let prize = match rng.random_bool(0.5) {
+                true => {
+                    let (a, b) = (number(rng, 0..101), number(rng, 0..101));
+                    IPoint::new(
+                        a * a_button.x + b * b_button.x,
+                        a * a_button.y + b * b_button.y,
+                    )
+                }
+                false => IPoint::new(number(rng, 1000..20000), number(rng, 1000..20000)),
+            };
+            ClawMachine {
+                prize,
+                a_button,
+                b_button,
+            }
+            .to_string()
+        })
+        .join("\n\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "Button A: X+94, Y+34
 Button B: X+22, Y+67
@@ -122,6 +246,22 @@ Prize: X=18641, Y=10279";
         Ok(())
     }
 
+    #[test]
+    fn should_report_bad_machine() {
+        let input = AdventInput::from_text(
+            "Button A: X+1, Y+2\nButton B: X+3, Y+4\nPrize: X=5, Y=6\n\nButton A: X+1, Y+2\nPrize: X=5, Y=6",
+        );
+        let Err(e) = Solution::new().load(&input) else {
+            panic!("Parsed a claw machine with no B button");
+        };
+        assert_eq!(
+            "Invalid claw machine specification at line 5",
+            e.to_string()
+        );
+        let input = AdventInput::from_text("Button A: X+1, Y+2\nButton B: X+3\nPrize: X=5, Y=6");
+        assert!(Solution::new().load(&input).is_err());
+    }
+
     #[test]
     fn should_calculate_winning_button_pushes() -> Result<()> {
         let machine =
@@ -141,14 +281,39 @@ Prize: X=18641, Y=10279";
         Ok(())
     }
 
+    #[test]
+    fn should_not_push_backwards() -> Result<()> {
+        let machine =
+            "Button A: X+2, Y+1\nButton B: X+1, Y+2\nPrize: X=0, Y=3".parse::<ClawMachine>()?;
+        assert_eq!(None, machine.get_pushes_to_prize(0));
+        let input = AdventInput::from_text(machine.to_string());
+        assert_eq!(Answer::from(0), Solution::new().solve_part_one(&input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_handle_collinear_buttons() -> Result<()> {
+        let machine = |prize: &str| {
+            format!("Button A: X+4, Y+2\nButton B: X+2, Y+1\nPrize: {prize}").parse::<ClawMachine>()
+        };
+        // One push of A costs 3 where two of B cost 2
+        assert_eq!(Some((0, 4)), machine("X=8, Y=4")?.get_pushes_to_prize(0));
+        assert_eq!(Some((0, 5)), machine("X=10, Y=5")?.get_pushes_to_prize(0));
+        assert_eq!(None, machine("X=8, Y=5")?.get_pushes_to_prize(0));
+        let machine =
+            "Button A: X+4, Y+4\nButton B: X+3, Y+3\nPrize: X=10, Y=10".parse::<ClawMachine>()?;
+        assert_eq!(Some((1, 2)), machine.get_pushes_to_prize(0));
+        assert_eq!(Some(5), machine.cost_to_win(0));
+        let machine =
+            "Button A: X+6, Y+0\nButton B: X+4, Y+0\nPrize: X=7, Y=0".parse::<ClawMachine>()?;
+        assert_eq!(None, machine.get_pushes_to_prize(0));
+        Ok(())
+    }
+
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let total: isize = EXAMPLE_INPUT
-            .split("\n\n")
-            .filter_map(|s| s.parse::<ClawMachine>().ok())
-            .filter_map(|m| m.cost_to_win(0))
-            .sum();
-        assert_eq!(480, total);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(Answer::from(480), Solution::new().solve_part_one(&input)?);
         Ok(())
     }
 
@@ -162,12 +327,35 @@ Prize: X=18641, Y=10279";
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let total: isize = EXAMPLE_INPUT
-            .split("\n\n")
-            .filter_map(|s| s.parse::<ClawMachine>().ok())
-            .filter_map(|m| m.cost_to_win(PRIZE_OFFSET))
-            .sum();
-        assert_eq!(875318608908, total);
+        let input = AdventInput::from_text(EXAMPLE_INPUT);
+        assert_eq!(
+            Answer::from(875318608908i64),
+            Solution::new().solve_part_two(&input)?
+        );
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn should_find_cheapest_pushes(u in 0..20isize, v in 0..20isize, w in 0..200isize) {
+            prop_assume!(u != 0 || v != 0);
+            let cost = |(a, b): (isize, isize)| a * A_COST + b * B_COST;
+            let brute = (0..=w)
+                .flat_map(|a| (0..=w).map(move |b| (a, b)))
+                .filter(|(a, b)| a * u + b * v == w)
+                .map(cost)
+                .min();
+            prop_assert_eq!(brute, cheapest_pushes(u, v, w).map(cost));
+        }
+
+        #[test]
+        fn should_round_trip(xys in prop::array::uniform6(0..100_000isize)) {
+            let machine = ClawMachine {
+                a_button: Vector::new(xys[0], xys[1]),
+                b_button: Vector::new(xys[2], xys[3]),
+                prize: IPoint::new(xys[4], xys[5]),
+            };
+            prop_assert_eq!(&machine, &machine.to_string().parse::<ClawMachine>().unwrap());
+        }
+    }
 }
@@ -1,3 +1,6 @@
+use crate::solver::{Measurable, Measure, Solver};
+use anyhow::{anyhow, Result};
+
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -11,3 +14,59 @@ pub mod day10;
 pub mod day11;
 pub mod day12;
 pub mod day13;
+
+// Every day there's a solution for, in order
+pub fn all() -> Vec<Box<dyn Measurable>> {
+    vec![
+        Measure::get(day01::Solution::new()),
+        Measure::get(day02::Solution::new()),
+        Measure::get(day03::Solution::new()),
+        Measure::get(day04::Solution::new()),
+        Measure::get(day05::Solution::new()),
+        Measure::get(day06::Solution::new()),
+        Measure::get(day07::Solution::new()),
+        Measure::get(day08::Solution::new()),
+        Measure::get(day09::Solution::new()),
+        Measure::get(day10::Solution::new()),
+        Measure::get(day11::Solution::new()),
+        Measure::get(day12::Solution::new()),
+        Measure::get(day13::Solution::new()),
+    ]
+}
+
+pub fn get(day: u8) -> Result<Box<dyn Measurable>> {
+    all()
+        .into_iter()
+        .find(|m| m.day() == day)
+        .ok_or(anyhow!("No solution for day {day}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::AdventInput;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn should_solve_generated_input(size in 1..20usize, seed: u64) {
+            for m in all() {
+                // Not every day has a generator
+                let Ok(text) = m.generate(size, seed) else {
+                    continue;
+                };
+                let (part1, part2) = m.solve(AdventInput::from_text(text), None);
+                let day = m.day();
+                prop_assert!(part1.solved().is_some(), "Day {day} part one: {part1}");
+                prop_assert!(part2.solved().is_some(), "Day {day} part two: {part2}");
+            }
+        }
+
+        #[test]
+        fn should_not_panic_on_garbage(s in "\\PC*") {
+            for m in all() {
+                let _ = m.check_input(AdventInput::from_text(s.as_str()));
+            }
+        }
+    }
+}
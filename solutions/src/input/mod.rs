@@ -0,0 +1,262 @@
+// Stop warning me about unused code in this file damnit
+#![allow(dead_code)]
+
+use anyhow::Result;
+use itertools::Itertools;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+pub mod parse;
+pub mod validate;
+
+#[cfg(feature = "embed-inputs")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_inputs.rs"));
+}
+
+/// Where a puzzle's text comes from.
+pub enum Source {
+    File(PathBuf),
+    Text(String),
+    Stdin,
+    // Baked into the binary with include_str!
+    Embedded(&'static str),
+}
+
+pub struct AdventInput {
+    source: Source,
+    trim_lines: bool,
+}
+impl AdventInput {
+    pub fn for_day(day: u8) -> AdventInput {
+        #[cfg(feature = "embed-inputs")]
+        if let Some(text) = embedded::input(day) {
+            return Self::embedded(text);
+        }
+        Self::from_file(format!("inputs/day{:02}.txt", day))
+    }
+
+    pub fn from_file(file: impl Into<PathBuf>) -> AdventInput {
+        Self::from_source(Source::File(file.into()))
+    }
+
+    pub fn from_text(text: impl Into<String>) -> AdventInput {
+        Self::from_source(Source::Text(text.into()))
+    }
+
+    pub fn from_stdin() -> AdventInput {
+        Self::from_source(Source::Stdin)
+    }
+
+    pub fn embedded(text: &'static str) -> AdventInput {
+        Self::from_source(Source::Embedded(text))
+    }
+
+    pub fn from_source(source: Source) -> AdventInput {
+        AdventInput {
+            source,
+            trim_lines: false,
+        }
+    }
+
+    // For inputs where stray whitespace at either end of a line never means anything
+    pub fn trim_lines(mut self) -> Self {
+        self.trim_lines = true;
+        self
+    }
+
+    // Everything else reads through here, so they all see the same text
+    pub fn get(&self) -> Result<String> {
+        let read = match &self.source {
+            Source::File(file) => fs::read_to_string(file)?,
+            Source::Text(text) => text.clone(),
+            Source::Stdin => read_stdin()?.to_string(),
+            Source::Embedded(text) => text.to_string(),
+        };
+        Ok(normalize(&read, self.trim_lines))
+    }
+
+    // A line at a time, for inputs too big to want in memory twice over.
+    // This skips the normalizing `get` does, so blank lines and stray whitespace are the caller's problem.
+    pub fn reader(&self) -> Result<Box<dyn BufRead + '_>> {
+        Ok(match &self.source {
+            Source::File(file) => Box::new(BufReader::new(File::open(file)?)),
+            Source::Text(text) => Box::new(text.as_bytes()),
+            Source::Stdin => Box::new(read_stdin()?.as_bytes()),
+            Source::Embedded(text) => Box::new(text.as_bytes()),
+        })
+    }
+
+    pub fn get_as<T>(&self) -> Result<T>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        Ok(T::from_str(&self.get()?)?)
+    }
+
+    pub fn get_csv(&self) -> Result<Vec<String>> {
+        self.get_split(',')
+    }
+
+    pub fn get_csv_as<T>(&self) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        self.get_split_as(',')
+    }
+
+    pub fn get_lines(&self) -> Result<Vec<String>> {
+        Ok(self.get()?.lines().map(|l| l.to_owned()).collect())
+    }
+
+    pub fn get_lines_as<T>(&self) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        let read = self.get()?;
+        read.lines()
+            .enumerate()
+            .map(|(i, l)| parse_piece(l, "line", i))
+            .collect()
+    }
+
+    pub fn get_grouped_as<T>(&self) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        self.get_split_str_as("\n\n")
+    }
+
+    // Every signed integer in the whole input, whatever is wrapped around them
+    pub fn get_ints<T: FromStr>(&self) -> Result<Vec<T>> {
+        Ok(parse::ints(&self.get()?)?)
+    }
+
+    pub fn get_ints_per_line<T: FromStr>(&self) -> Result<Vec<Vec<T>>> {
+        let read = self.get()?;
+        Ok(read
+            .lines()
+            .enumerate()
+            .map(|(i, l)| parse::ints(l).map_err(|e| e.below(i)))
+            .collect::<Result<_, _>>()?)
+    }
+
+    pub fn get_split(&self, pat: char) -> Result<Vec<String>> {
+        let read = self.get()?;
+        Ok(read.split(pat).map(|x| x.to_owned()).collect())
+    }
+
+    pub fn get_split_str(&self, pat: &str) -> Result<Vec<String>> {
+        let read = self.get()?;
+        Ok(read.split(pat).map(|x| x.to_owned()).collect())
+    }
+
+    pub fn get_split_as<T>(&self, pat: char) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        let read = self.get()?;
+        read.split(pat)
+            .enumerate()
+            .map(|(i, x)| parse_piece(x, "item", i))
+            .collect()
+    }
+
+    pub fn get_split_str_as<T>(&self, pat: &str) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        anyhow::Error: From<T::Err>,
+    {
+        let read = self.get()?;
+        read.split(pat)
+            .enumerate()
+            .map(|(i, x)| parse_piece(x, "item", i))
+            .collect()
+    }
+}
+
+// Says which piece was wrong, since the error from T alone rarely does
+fn parse_piece<T>(s: &str, what: &str, index: usize) -> Result<T>
+where
+    T: FromStr,
+    anyhow::Error: From<T::Err>,
+{
+    s.parse::<T>()
+        .map_err(|e| anyhow::Error::from(e).context(format!("Bad {what} {}: {s:?}", index + 1)))
+}
+
+// Stdin can only be read once, but perf (for one) reads the input over and over
+fn read_stdin() -> Result<&'static str> {
+    static STDIN: OnceLock<String> = OnceLock::new();
+    if let Some(read) = STDIN.get() {
+        return Ok(read);
+    }
+    let mut read = String::new();
+    io::stdin().read_to_string(&mut read)?;
+    Ok(STDIN.get_or_init(|| read))
+}
+
+// Windows line endings, and however many newlines an editor felt like leaving at the end
+fn normalize(read: &str, trim_lines: bool) -> String {
+    let read = read.replace("\r\n", "\n");
+    let read = read.trim_end_matches('\n');
+    match trim_lines {
+        true => read.lines().map(str::trim).join("\n"),
+        false => read.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_normalize_line_endings() {
+        assert_eq!("1 2\n\n3 4", normalize("1 2\r\n\r\n3 4\r\n\r\n", false));
+        assert_eq!("1 2\n3 4", normalize("1 2\n3 4", false));
+        assert_eq!(" 1 2 \n3 4", normalize(" 1 2 \n3 4\n", false));
+    }
+
+    #[test]
+    fn should_read_from_any_source() -> Result<()> {
+        let text = "3   4\r\n4   3\r\n";
+        assert_eq!(
+            vec![vec![3, 4], vec![4, 3]],
+            AdventInput::from_text(text).get_ints_per_line::<usize>()?
+        );
+        assert_eq!("3   4\n4   3", AdventInput::embedded(text).get()?);
+        assert!(AdventInput::from_file("inputs/day99.txt").get().is_err());
+        let lines: Vec<_> = AdventInput::from_text(text)
+            .reader()?
+            .lines()
+            .try_collect()?;
+        assert_eq!(vec!["3   4", "4   3"], lines);
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_bad_pieces() -> Result<()> {
+        let input = AdventInput::from_text("1\n2\nthree\n4");
+        let e = input.get_lines_as::<usize>().unwrap_err();
+        assert_eq!("Bad line 3: \"three\"", e.to_string());
+        assert!(input.get_split_as::<usize>(',').is_err());
+        assert_eq!(
+            vec![1, 2, 3],
+            AdventInput::from_text("1,2,3").get_csv_as::<u8>()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_trim_lines() {
+        assert_eq!("1 2\n\n3 4", normalize(" 1 2 \r\n  \r\n3 4\t\n", true));
+    }
+}
@@ -0,0 +1,342 @@
+// Little building blocks for the formats AoC keeps coming back to.
+// Everything reports where it went wrong, since "invalid digit found in string" is useless on a 1000 line input.
+use std::{any::type_name, error::Error, fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based, like an editor would show it
+    pub line: usize,
+    /// 1-based, counted in characters rather than bytes
+    pub column: usize,
+    pub message: String,
+}
+impl ParseError {
+    fn at(s: &str, offset: usize, message: impl Into<String>) -> Self {
+        let before = &s[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    // For errors from a chunk that started further down the input
+    pub fn below(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+impl Error for ParseError {}
+
+// Byte offset of a slice that was carved out of `s`
+fn offset(s: &str, part: &str) -> usize {
+    part.as_ptr() as usize - s.as_ptr() as usize
+}
+
+fn field<T: FromStr>(s: &str, part: &str) -> Result<T, ParseError> {
+    part.parse().map_err(|_| {
+        ParseError::at(
+            s,
+            offset(s, part),
+            format!("'{part}' isn't a valid {}", type_name::<T>()),
+        )
+    })
+}
+
+// Length of the number (optional sign, then digits) at the start of `s`, if there is one
+fn number_len(s: &str, signed: bool) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let sign = match bytes.first() {
+        Some(b'+') => 1,
+        Some(b'-') if signed => 1,
+        _ => 0,
+    };
+    let digits = bytes[sign..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    (digits > 0).then_some(sign + digits)
+}
+
+// A '-' is only a sign if the type can be negative; otherwise "3-4" would blow up as a usize
+fn is_signed<T: FromStr>() -> bool {
+    "-1".parse::<T>().is_ok()
+}
+
+/// Every integer in `s`, ignoring whatever is in between them.
+pub fn ints<T: FromStr>(s: &str) -> Result<Vec<T>, ParseError> {
+    let signed = is_signed::<T>();
+    let mut found = vec![];
+    let mut i = 0;
+    while i < s.len() {
+        match number_len(&s[i..], signed) {
+            Some(len) => {
+                found.push(field(s, &s[i..i + len])?);
+                i += len;
+            }
+            None => i += s[i..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    Ok(found)
+}
+
+/// "190: 10 19" style lines, a key followed by a whitespace separated list of values.
+pub fn key_list<K: FromStr, V: FromStr>(s: &str, sep: &str) -> Result<(K, Vec<V>), ParseError> {
+    let Some((key, values)) = s.split_once(sep) else {
+        return Err(ParseError::at(s, s.len(), format!("expected '{sep}'")));
+    };
+    let key = field(s, key.trim())?;
+    let values = values
+        .split_whitespace()
+        .map(|v| field(s, v))
+        .collect::<Result<_, _>>()?;
+    Ok((key, values))
+}
+
+/// The numbers after each label in turn, e.g. `["X", "Y"]` for "Button A: X+94, Y+34" or "Prize: X=8400, Y=5400".
+pub fn labeled<T: FromStr>(s: &str, labels: &[&str]) -> Result<Vec<T>, ParseError> {
+    let signed = is_signed::<T>();
+    let mut found = vec![];
+    let mut from = 0;
+    for label in labels {
+        // The label has to be a whole word, and be followed by a number (give or take an '=' or ':')
+        let Some(start) = s[from..].match_indices(label).find_map(|(i, _)| {
+            let (i, end) = (from + i, from + i + label.len());
+            let whole_word = !s[..i].ends_with(|c: char| c.is_alphanumeric())
+                && !s[end..].starts_with(|c: char| c.is_alphabetic());
+            whole_word.then_some(end)
+        }) else {
+            return Err(ParseError::at(s, from, format!("expected {label}")));
+        };
+        let rest = s[start..].trim_start_matches(['=', ':']).trim_start();
+        let at = offset(s, rest);
+        let Some(len) = number_len(rest, signed) else {
+            return Err(ParseError::at(
+                s,
+                at,
+                format!("expected a number after {label}"),
+            ));
+        };
+        found.push(field(s, &rest[..len])?);
+        from = at + len;
+    }
+    Ok(found)
+}
+
+/// Tuples that can be built from a fixed number of text fields.
+pub trait Tuple: Sized {
+    const LEN: usize;
+    fn from_fields(s: &str, fields: &[&str]) -> Result<Self, ParseError>;
+}
+macro_rules! impl_tuple {
+    ($len:expr; $($t:ident $i:tt),*) => {
+        impl<$($t: FromStr),*> Tuple for ($($t,)*) {
+            const LEN: usize = $len;
+            fn from_fields(s: &str, fields: &[&str]) -> Result<Self, ParseError> {
+                Ok(($(field::<$t>(s, fields[$i])?,)*))
+            }
+        }
+    };
+}
+impl_tuple!(2; A 0, B 1);
+impl_tuple!(3; A 0, B 1, C 2);
+impl_tuple!(4; A 0, B 1, C 2, D 3);
+
+fn tuple<T: Tuple>(s: &str, fields: &[&str], what: &str) -> Result<T, ParseError> {
+    if fields.len() != T::LEN {
+        // Point at the first field too many, or the end of the line if we ran out
+        let at = fields.get(T::LEN).map_or(s.len(), |f| offset(s, f));
+        return Err(ParseError::at(
+            s,
+            at,
+            format!("expected {} {what}, found {}", T::LEN, fields.len()),
+        ));
+    }
+    T::from_fields(s, fields)
+}
+
+/// Exactly as many typed fields as the tuple has, separated by `sep`, e.g. `(usize, usize)` from "47|53".
+pub fn split_tuple<T: Tuple>(s: &str, sep: &str) -> Result<T, ParseError> {
+    let fields: Vec<_> = s.split(sep).map(str::trim).collect();
+    tuple(s, &fields, &format!("fields separated by '{sep}'"))
+}
+
+/// Like [split_tuple], but the fields are separated by any amount of whitespace.
+pub fn word_tuple<T: Tuple>(s: &str) -> Result<T, ParseError> {
+    let fields: Vec<_> = s.split_whitespace().collect();
+    tuple(s, &fields, "words")
+}
+
+/// Any number of typed fields separated by `sep`, e.g. "75,47,61,53,29".
+pub fn split_list<T: FromStr>(s: &str, sep: &str) -> Result<Vec<T>, ParseError> {
+    s.split(sep).map(|f| field(s, f.trim())).collect()
+}
+
+/// Like [split_list], but the fields are separated by any amount of whitespace.
+pub fn word_list<T: FromStr>(s: &str) -> Result<Vec<T>, ParseError> {
+    s.split_whitespace().map(|f| field(s, f)).collect()
+}
+
+/// Runs `f` over every non-blank line, fixing up the line numbers in any error.
+pub fn lines_with<T>(
+    s: &str,
+    mut f: impl FnMut(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| f(l).map_err(|e| e.below(i)))
+        .collect()
+}
+
+/// A run of lines between blank lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// How many lines of the input come before this one
+    pub line: usize,
+    pub text: &'a str,
+}
+impl<'a> Section<'a> {
+    pub fn lines_with<T>(
+        &self,
+        f: impl FnMut(&str) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        lines_with(self.text, f).map_err(|e| e.below(self.line))
+    }
+}
+
+/// Splits `s` on blank lines, however many of them there are.
+pub fn sections(s: &str) -> Vec<Section<'_>> {
+    let mut found = vec![];
+    let mut current: Option<(usize, usize, usize)> = None;
+    for (i, l) in s.lines().enumerate() {
+        if l.trim().is_empty() {
+            found.extend(current.take());
+            continue;
+        }
+        let end = offset(s, l) + l.len();
+        current = match current {
+            Some((line, start, _)) => Some((line, start, end)),
+            None => Some((i, offset(s, l), end)),
+        };
+    }
+    found.extend(current);
+    found
+        .into_iter()
+        .map(|(line, start, end)| Section {
+            line,
+            text: &s[start..end],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_ints() -> Result<(), ParseError> {
+        assert_eq!(vec![94, 34], ints::<i64>("Button A: X+94, Y+34")?);
+        assert_eq!(vec![-3, 7, -12], ints::<i32>("p=-3,7 v=-12")?);
+        // No negative usizes, so those dashes are just separators
+        assert_eq!(vec![3, 4], ints::<usize>("3-4")?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_point_at_bad_numbers() {
+        let err = ints::<u8>("1 2\n3 300").unwrap_err();
+        assert_eq!((2, 3), (err.line, err.column));
+        assert_eq!("line 2, column 3: '300' isn't a valid u8", err.to_string());
+    }
+
+    #[test]
+    fn should_parse_key_lists() -> Result<(), ParseError> {
+        assert_eq!(
+            (3267usize, vec![81usize, 40, 27]),
+            key_list("3267: 81 40 27", ":")?
+        );
+        let err = key_list::<usize, usize>("3267 81 40", ":").unwrap_err();
+        assert_eq!((1, 11), (err.line, err.column));
+        let err = key_list::<usize, usize>("3267: 81 x", ":").unwrap_err();
+        assert_eq!((1, 10), (err.line, err.column));
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_labeled_numbers() -> Result<(), ParseError> {
+        assert_eq!(
+            vec![94, 34],
+            labeled::<i64>("Button A: X+94, Y+34", &["X", "Y"])?
+        );
+        assert_eq!(
+            vec![8400, -5400],
+            labeled::<i64>("Prize: X=8400, Y=-5400", &["X", "Y"])?
+        );
+        let err = labeled::<i64>("Prize: X=8400, Z=5400", &["X", "Y"]).unwrap_err();
+        assert_eq!((1, 14), (err.line, err.column));
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_tuples() -> Result<(), ParseError> {
+        assert_eq!((47usize, 53usize), split_tuple("47|53", "|")?);
+        assert_eq!((3isize, 4isize), word_tuple("3   4")?);
+        assert_eq!(('a', 1u8, true), split_tuple("a, 1, true", ",")?);
+        let err = word_tuple::<(isize, isize)>("3   4   5").unwrap_err();
+        assert_eq!(
+            "line 1, column 9: expected 2 words, found 3",
+            err.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_lists() -> Result<(), ParseError> {
+        assert_eq!(vec![75, 47, 61], split_list::<usize>("75,47, 61", ",")?);
+        assert_eq!(vec![7, -6, 4], word_list::<isize>("7  -6 4")?);
+        let err = split_list::<usize>("1,2x,3", ",").unwrap_err();
+        assert_eq!(
+            "line 1, column 3: '2x' isn't a valid usize",
+            err.to_string()
+        );
+        let err = split_list::<usize>("1,,3", ",").unwrap_err();
+        assert_eq!("line 1, column 3: '' isn't a valid usize", err.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn should_split_sections() {
+        let input = "1|2\n3|x\n\n\n1,2,300\n";
+        let found = sections(input);
+        assert_eq!(
+            vec![
+                Section {
+                    line: 0,
+                    text: "1|2\n3|x"
+                },
+                Section {
+                    line: 4,
+                    text: "1,2,300"
+                }
+            ],
+            found
+        );
+        let err = found[0]
+            .lines_with(|l| split_tuple::<(u8, u8)>(l, "|"))
+            .unwrap_err();
+        assert_eq!((2, 3), (err.line, err.column));
+        let err = found[1].lines_with(ints::<u8>).unwrap_err();
+        assert_eq!((5, 5), (err.line, err.column));
+    }
+}
@@ -0,0 +1,91 @@
+// Checks for the assumptions the solvers make about their input, so bad input gets an error instead of a panic.
+use anyhow::{anyhow, Context, Result};
+use std::{fmt::Display, ops::RangeInclusive};
+
+/// Every line is the same (non-zero) width.  Gives back the width and height.
+pub fn rectangular(s: &str) -> Result<(usize, usize)> {
+    let mut lines = s.lines().enumerate();
+    let Some((_, first)) = lines.next() else {
+        return Err(anyhow!("Grid is empty"));
+    };
+    let width = first.chars().count();
+    if width == 0 {
+        return Err(anyhow!("Grid starts with a blank line"));
+    }
+    let mut height = 1;
+    for (i, l) in lines {
+        let w = l.chars().count();
+        if w != width {
+            return Err(anyhow!(
+                "Grid isn't rectangular: line {} is {w} wide, but line 1 is {width}",
+                i + 1
+            ));
+        }
+        height += 1;
+    }
+    Ok((width, height))
+}
+
+pub fn non_empty<T>(items: &[T], what: &str) -> Result<()> {
+    match items.is_empty() {
+        true => Err(anyhow!("{what} is empty")),
+        false => Ok(()),
+    }
+}
+
+pub fn in_range<T: PartialOrd + Display>(
+    value: T,
+    range: RangeInclusive<T>,
+    what: &str,
+) -> Result<()> {
+    match range.contains(&value) {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "{what} is {value}, but should be between {} and {}",
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+/// Runs `check` on every item, saying which one it was if it fails.
+pub fn each<T>(items: &[T], what: &str, check: impl Fn(&T) -> Result<()>) -> Result<()> {
+    for (i, item) in items.iter().enumerate() {
+        check(item).with_context(|| format!("{what} {} is invalid", i + 1))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_check_rectangular() -> Result<()> {
+        assert_eq!((3, 2), rectangular("abc\ndef")?);
+        let err = rectangular("abc\nde\nfgh").unwrap_err();
+        assert_eq!(
+            "Grid isn't rectangular: line 2 is 2 wide, but line 1 is 3",
+            err.to_string()
+        );
+        assert!(rectangular("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_check_ranges() {
+        assert!(in_range(5, 0..=9, "Height").is_ok());
+        let err = in_range(12, 0..=9, "Height").unwrap_err();
+        assert_eq!(
+            "Height is 12, but should be between 0 and 9",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_say_which_item_failed() {
+        let reports = vec![vec![1, 2], vec![]];
+        let err = each(&reports, "Report", |r| non_empty(r, "Report")).unwrap_err();
+        assert_eq!("Report 2 is invalid: Report is empty", format!("{err:#}"));
+    }
+}
@@ -0,0 +1,15 @@
+#![allow(clippy::zero_prefixed_literal)]
+extern crate anyhow;
+extern crate impl_ops;
+
+pub mod answer;
+pub mod common;
+mod common_ops;
+pub mod crosscheck;
+pub mod days;
+pub mod input;
+pub mod memory;
+pub mod scaling;
+pub mod solver;
+pub mod visualize;
+pub mod watch;
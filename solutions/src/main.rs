@@ -1,23 +1,63 @@
-extern crate anyhow;
-extern crate impl_ops;
-
-use anyhow::Result;
-use clap::{arg, command, Parser, Subcommand};
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Parser, Subcommand};
+use colored::Colorize;
 use itertools::Itertools;
 use prettytable::{color, format::Alignment, row, Attr, Cell, Row, Table};
-use solver::{Measure, Solver};
+use serde::Serialize;
+use solutions::{
+    answer::Answer,
+    crosscheck::{self, Verdict},
+    days,
+    input::AdventInput,
+    memory::{format_bytes, CountingAllocator},
+    scaling::{self, Sample},
+    solver::{Measurable, Outcome},
+    visualize::{DirectorySink, FrameFormat, TerminalSink},
+    watch,
+};
+use std::{path::PathBuf, time::Duration};
+use tracing::level_filters::LevelFilter;
 
-mod common;
-mod common_ops;
-mod days;
-mod input;
-mod solver;
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Only solve this day
+    #[arg(short, long)]
+    day: Option<u8>,
+    /// Read the puzzle input from this file instead, or from stdin if it's "-"
+    #[arg(short, long, requires = "day")]
+    input: Option<PathBuf>,
+    /// Print the answers as JSON
+    #[arg(long)]
+    json: bool,
+    /// Also explain how the answers were reached, for days that can
+    #[arg(long, requires = "day", conflicts_with = "json")]
+    explain: bool,
+    /// Log more (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Don't log anything at all
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Give up on any single part after this many seconds
+    #[arg(short, long, global = true, value_parser = parse_seconds)]
+    timeout: Option<Duration>,
+}
+impl Cli {
+    fn log_level(&self) -> LevelFilter {
+        match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::OFF,
+            (_, 0) => LevelFilter::WARN,
+            (_, 1) => LevelFilter::INFO,
+            (_, 2) => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -27,55 +67,213 @@ enum Commands {
         fine: bool,
         #[arg(short, long, default_value_t = 10)]
         iterations: u8,
+        /// Also report peak heap usage and allocation count for each part
+        #[arg(short, long)]
+        memory: bool,
+        /// Read and parse each input once up front, so only the parts themselves are timed
+        #[arg(short, long)]
+        cached: bool,
+        /// Time this day over generated inputs of increasing size instead, to see how it scales
+        #[arg(long, value_name = "DAY")]
+        scale: Option<u8>,
+        /// Input sizes to try with --scale; what a size means depends on the day
+        #[arg(long, value_delimiter = ',', default_values_t = [25, 50, 100, 200, 400], requires = "scale")]
+        sizes: Vec<usize>,
+        /// Seed for the generated inputs
+        #[arg(long, default_value_t = 0, requires = "scale")]
+        seed: u64,
+        /// Also write the --scale timings to this CSV file, for plotting
+        #[arg(long, requires = "scale")]
+        csv: Option<PathBuf>,
+    },
+    Visualize {
+        day: u8,
+        /// Write frames to this directory instead of animating them in the terminal
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        /// Write PPM images instead of text frames (only with --out)
+        #[arg(long)]
+        ppm: bool,
+        /// Milliseconds between frames in the terminal
+        #[arg(short, long, default_value_t = 50)]
+        delay: u64,
+    },
+    /// Re-run a day's examples and real input whenever its source or input changes
+    Watch { day: u8 },
+    /// Check a day's fast implementations against their slow references
+    Crosscheck {
+        day: u8,
+        /// Check this file instead of the day's input, or stdin if it's "-"
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Print a random input for a day, e.g. to pipe into `--input -`
+    Gen {
+        day: u8,
+        /// Roughly how big to make it; what that means depends on the day
+        #[arg(short, long, default_value_t = 100)]
+        size: usize,
+        /// The same seed always gives the same input
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
     },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    // Logs go to stderr so they never get mixed up with the answers
+    tracing_subscriber::fmt()
+        .with_max_level(cli.log_level())
+        .with_writer(std::io::stderr)
+        .init();
     match cli.command {
-        Some(Commands::Perf { fine, iterations }) => perf(fine, iterations)?,
-        _ => solve()?,
+        Some(Commands::Perf {
+            fine,
+            iterations,
+            scale: Some(day),
+            sizes,
+            seed,
+            csv,
+            ..
+        }) => scale(day, &sizes, seed, iterations, fine, csv, cli.timeout)?,
+        Some(Commands::Perf {
+            fine,
+            iterations,
+            memory,
+            cached,
+            ..
+        }) => perf(fine, iterations, memory, cached, cli.timeout)?,
+        Some(Commands::Visualize {
+            day,
+            out,
+            ppm,
+            delay,
+        }) => visualize(day, out, ppm, delay)?,
+        Some(Commands::Watch { day }) => watch::watch(day)?,
+        Some(Commands::Crosscheck { day, input }) => crosscheck(day, input)?,
+        Some(Commands::Gen { day, size, seed }) => {
+            println!("{}", days::get(day)?.generate(size, seed)?)
+        }
+        _ => solve(cli.day, cli.input, cli.json, cli.explain, cli.timeout)?,
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct SolveRecord {
+    day: u8,
+    part: u8,
+    title: String,
+    status: &'static str,
+    answer: Option<Answer>,
+    error: Option<String>,
+}
+impl SolveRecord {
+    fn new(m: &dyn Measurable, part: u8, outcome: Outcome<Answer>) -> Self {
+        let (status, answer, error) = match outcome {
+            Outcome::Solved(a) => ("solved", Some(a), None),
+            Outcome::Failed(e) => ("failed", None, Some(format!("{e:#}"))),
+            Outcome::TimedOut => ("timeout", None, None),
+        };
+        SolveRecord {
+            day: m.day(),
+            part,
+            title: m.title(),
+            status,
+            answer,
+            error,
+        }
+    }
+}
+
+fn solve(
+    day: Option<u8>,
+    input: Option<PathBuf>,
+    json: bool,
+    explain: bool,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let solutions = match day {
+        Some(day) => vec![days::get(day)?],
+        None => days::all(),
+    };
+    let mut records = vec![];
+    for m in solutions {
+        let (part1, part2) = m.solve(input_for(m.as_ref(), input.as_ref()), timeout);
+        if json {
+            records.push(SolveRecord::new(m.as_ref(), 1, part1));
+            records.push(SolveRecord::new(m.as_ref(), 2, part2));
+        } else {
+            println!("{}: {}", m.describe_part_one(), part1);
+            println!("{}: {}", m.describe_part_two(), part2);
+        }
+        if explain {
+            println!("\n{}", m.explain(input_for(m.as_ref(), input.as_ref()))?);
+        }
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
     }
     Ok(())
 }
 
-fn solve() -> Result<()> {
-    days::day01::Solution::new().solve()?;
-    days::day02::Solution::new().solve()?;
-    days::day03::Solution::new().solve()?;
-    days::day04::Solution::new().solve()?;
-    days::day05::Solution::new().solve()?;
-    days::day06::Solution::new().solve()?;
-    days::day07::Solution::new().solve()?;
-    days::day08::Solution::new().solve()?;
-    days::day09::Solution::new().solve()?;
-    days::day10::Solution::new().solve()?;
-    days::day11::Solution::new().solve()?;
-    days::day12::Solution::new().solve()?;
-    days::day13::Solution::new().solve()?;
+fn input_for(m: &dyn Measurable, path: Option<&PathBuf>) -> AdventInput {
+    match path {
+        Some(path) if path.as_os_str() == "-" => AdventInput::from_stdin(),
+        Some(path) => AdventInput::from_file(path),
+        None => m.input(),
+    }
+}
+
+fn crosscheck(day: u8, input: Option<PathBuf>) -> Result<()> {
+    let m = days::get(day)?;
+    let verdicts = m.crosscheck(input_for(m.as_ref(), input.as_ref()))?;
+    if verdicts.is_empty() {
+        println!("Day {day:02} has no cross-checks");
+    }
+    for (name, verdict) in &verdicts {
+        match verdict {
+            Verdict::Agreed(answer) => println!("{name}: {} ({answer})", "OK".green()),
+            Verdict::Diverged(d) => println!(
+                "{name}: {}\n  fast:      {}\n  reference: {}\n  smallest input that still diverges:\n{}",
+                "DIVERGED".red().bold(),
+                d.fast,
+                d.reference,
+                d.input
+            ),
+        }
+    }
+    crosscheck::ensure_agreed(&verdicts)
+}
+
+fn visualize(day: u8, out: Option<PathBuf>, ppm: bool, delay: u64) -> Result<()> {
+    let solution = days::get(day)?;
+    let Some(dir) = out else {
+        return solution.visualize(&mut TerminalSink::new(Duration::from_millis(delay)));
+    };
+    let format = if ppm {
+        FrameFormat::Ppm
+    } else {
+        FrameFormat::Text
+    };
+    let mut sink = DirectorySink::new(dir.clone(), format)?;
+    solution.visualize(&mut sink)?;
+    println!("Wrote {} frames to {}", sink.count(), dir.display());
     Ok(())
 }
 
-fn perf(fine: bool, iterations: u8) -> Result<()> {
+fn perf(
+    fine: bool,
+    iterations: u8,
+    memory: bool,
+    cached: bool,
+    timeout: Option<Duration>,
+) -> Result<()> {
     let fmt_func = match fine {
         true => format_fine,
         false => format_rough,
     };
-    let measures: Vec<_> = vec![
-        Measure::get(days::day01::Solution::new()),
-        Measure::get(days::day02::Solution::new()),
-        Measure::get(days::day03::Solution::new()),
-        Measure::get(days::day04::Solution::new()),
-        Measure::get(days::day05::Solution::new()),
-        Measure::get(days::day06::Solution::new()),
-        Measure::get(days::day07::Solution::new()),
-        Measure::get(days::day08::Solution::new()),
-        Measure::get(days::day09::Solution::new()),
-        Measure::get(days::day10::Solution::new()),
-        Measure::get(days::day11::Solution::new()),
-        Measure::get(days::day12::Solution::new()),
-        Measure::get(days::day13::Solution::new()),
-    ];
+    let measures = days::all();
     let count = measures.len();
 
     println!("Generating performance statistics...");
@@ -87,24 +285,29 @@ fn perf(fine: bool, iterations: u8) -> Result<()> {
         results.push((
             m.title(),
             m.describe_part_one(),
-            m.time_part_one(iterations)?,
+            m.time_part_one(iterations, cached, timeout),
+            memory.then(|| m.measure_part_one(cached, timeout)),
         ));
         results.push((
             m.title(),
             m.describe_part_two(),
-            m.time_part_two(iterations)?,
+            m.time_part_two(iterations, cached, timeout),
+            memory.then(|| m.measure_part_two(cached, timeout)),
         ));
     }
     println!("\nDone.");
 
-    // Get some rough stats
-    let len = results.len();
-    let (i1, i2, i3) = (len / 2, (len / 4) * 2, (len * 90) / 100);
+    // Get some rough stats, ignoring anything that didn't finish
     let ranked = results
         .iter()
-        .sorted_by(|a, b| a.1.cmp(&b.1))
-        .map(|(_, _, d)| d.as_secs_f32())
+        .filter_map(|(_, _, o, _)| o.solved().map(|d| d.as_secs_f32()))
+        .sorted_by(|a, b| a.total_cmp(b))
         .collect_vec();
+    if ranked.is_empty() {
+        return Err(anyhow!("No solutions finished"));
+    }
+    let len = ranked.len();
+    let (i1, i2, i3) = (len / 2, (len / 4) * 2, (len * 90) / 100);
     let (p1, p2, p3) = (ranked[i1], ranked[i2], ranked[i3]);
 
     let get_quartile_color = |d: f32| match d {
@@ -121,22 +324,36 @@ fn perf(fine: bool, iterations: u8) -> Result<()> {
     let mut table = Table::new();
     table.set_format(*prettytable::format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
 
-    table.set_titles(row!["Year", "Day", "Title", "Part", "Time"]);
+    let mut titles = row!["Year", "Day", "Title", "Part", "Time"];
+    if memory {
+        titles.add_cell(Cell::new("Peak Heap"));
+        titles.add_cell(Cell::new("Allocs"));
+    }
+    let columns = titles.len();
+    table.set_titles(titles);
 
-    for (title, key, res) in results {
+    for (title, key, res, mem) in results {
         let s = key.split(' ').collect_vec();
         let (day, part) = (s[1], s[3]);
-        let dur = res.as_secs_f32();
-        table.add_row(Row::new(vec![
+        let time = outcome_cell(&res, |d| {
+            let dur = d.as_secs_f32();
+            Cell::new(&fmt_func(&dur)).with_style(Attr::ForegroundColor(get_quartile_color(dur)))
+        });
+        let mut row = Row::new(vec![
             Cell::new("2023"),
             Cell::new(day),
             Cell::new(&title),
             Cell::new(part),
-            Cell::new(&fmt_func(&dur)).with_style(Attr::ForegroundColor(get_quartile_color(dur))),
-        ]));
+            time,
+        ]);
+        if let Some(mem) = mem {
+            row.add_cell(outcome_cell(&mem, |m| Cell::new(&format_bytes(m.peak))));
+            row.add_cell(outcome_cell(&mem, |m| Cell::new(&m.count.to_string())));
+        }
+        table.add_row(row);
     }
 
-    table.add_row(Row::new(vec![Cell::new("").with_hspan(5)]));
+    table.add_row(Row::new(vec![Cell::new("").with_hspan(columns)]));
 
     table.add_row(Row::new(vec![
         Cell::new_align("Total", Alignment::RIGHT)
@@ -162,6 +379,82 @@ fn perf(fine: bool, iterations: u8) -> Result<()> {
     Ok(())
 }
 
+fn scale(
+    day: u8,
+    sizes: &[usize],
+    seed: u64,
+    iterations: u8,
+    fine: bool,
+    csv: Option<PathBuf>,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let fmt_func = match fine {
+        true => format_fine,
+        false => format_rough,
+    };
+    let m = days::get(day)?;
+    println!("Timing {} on generated inputs...", m.title());
+
+    let mut samples = vec![];
+    for &size in sizes {
+        print!("\rSize {size}...");
+        let (part_one, part_two) = m.time_generated(size, seed, iterations, timeout)?;
+        samples.push(Sample {
+            size,
+            part_one,
+            part_two,
+        });
+    }
+    println!("\nDone.");
+
+    let mut table = Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+    table.set_titles(row!["Size", "Part 1", "Part 2"]);
+    let time = |o: &Outcome<Duration>| outcome_cell(o, |d| Cell::new(&fmt_func(&d.as_secs_f32())));
+    for s in &samples {
+        table.add_row(Row::new(vec![
+            Cell::new(&s.size.to_string()),
+            time(&s.part_one),
+            time(&s.part_two),
+        ]));
+    }
+    table.printstd();
+
+    for (part, points) in [
+        (
+            m.describe_part_one(),
+            scaling::solved(&samples, |s| &s.part_one),
+        ),
+        (
+            m.describe_part_two(),
+            scaling::solved(&samples, |s| &s.part_two),
+        ),
+    ] {
+        match scaling::fit_exponent(&points) {
+            Some(k) => println!("{part}: roughly O(n^{k:.2})"),
+            None => println!("{part}: not enough timings to tell"),
+        }
+    }
+
+    if let Some(path) = csv {
+        std::fs::write(&path, scaling::to_csv(&samples))?;
+        println!("Wrote timings to {}", path.display());
+    }
+    Ok(())
+}
+
+fn outcome_cell<T>(outcome: &Outcome<T>, fmt: impl Fn(&T) -> Cell) -> Cell {
+    match outcome {
+        Outcome::Solved(v) => fmt(v),
+        Outcome::Failed(_) => Cell::new("FAILED").with_style(Attr::ForegroundColor(color::RED)),
+        Outcome::TimedOut => Cell::new("TIMEOUT").with_style(Attr::ForegroundColor(color::RED)),
+    }
+}
+
+fn parse_seconds(s: &str) -> Result<Duration> {
+    Ok(Duration::try_from_secs_f64(s.parse()?)?)
+}
+
 fn format_rough(dur: &f32) -> String {
     format!("{dur:0.03}s")
 }
@@ -0,0 +1,106 @@
+// Times a day over generated inputs of growing size, to see whether it's linear, quadratic or worse.
+// The exponent is relative to whatever "size" means to that day's generator (a grid's width, a number of lines...).
+use crate::solver::Outcome;
+use std::{fmt::Write, time::Duration};
+
+pub struct Sample {
+    pub size: usize,
+    pub part_one: Outcome<Duration>,
+    pub part_two: Outcome<Duration>,
+}
+
+/// Least squares slope of log(time) against log(size), i.e. the `k` in O(n^k).
+/// Needs at least two different sizes that actually took some time.
+pub fn fit_exponent(points: &[(usize, Duration)]) -> Option<f64> {
+    let logs: Vec<(f64, f64)> = points
+        .iter()
+        .filter(|(size, time)| *size > 0 && !time.is_zero())
+        .map(|(size, time)| ((*size as f64).ln(), time.as_secs_f64().ln()))
+        .collect();
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = logs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    // Also catches fewer than two points, where the variance is zero (or NaN)
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+/// Every part's timings that finished, ready for [fit_exponent].
+pub fn solved(
+    samples: &[Sample],
+    part: impl Fn(&Sample) -> &Outcome<Duration>,
+) -> Vec<(usize, Duration)> {
+    samples
+        .iter()
+        .filter_map(|s| part(s).solved().map(|&d| (s.size, d)))
+        .collect()
+}
+
+/// Seconds per part for each size; anything that didn't finish is left blank.
+pub fn to_csv(samples: &[Sample]) -> String {
+    let seconds = |o: &Outcome<Duration>| {
+        o.solved()
+            .map_or(String::new(), |d| d.as_secs_f64().to_string())
+    };
+    let mut csv = String::from("size,part_one,part_two\n");
+    for s in samples {
+        // Can't fail, it's a String
+        let _ = writeln!(
+            csv,
+            "{},{},{}",
+            s.size,
+            seconds(&s.part_one),
+            seconds(&s.part_two)
+        );
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(points: &[(usize, u64)]) -> Vec<(usize, Duration)> {
+        points
+            .iter()
+            .map(|&(size, ms)| (size, Duration::from_millis(ms)))
+            .collect()
+    }
+
+    #[test]
+    fn should_fit_exponent() {
+        let linear = fit_exponent(&millis(&[(10, 5), (20, 10), (40, 20), (80, 40)])).unwrap();
+        assert!((linear - 1.0).abs() < 1e-9);
+        let quadratic = fit_exponent(&millis(&[(10, 1), (20, 4), (40, 16)])).unwrap();
+        assert!((quadratic - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_need_two_sizes() {
+        assert_eq!(None, fit_exponent(&millis(&[(10, 5)])));
+        assert_eq!(None, fit_exponent(&millis(&[(10, 5), (10, 6)])));
+        assert_eq!(None, fit_exponent(&millis(&[(10, 0), (20, 0)])));
+    }
+
+    #[test]
+    fn should_write_csv() {
+        let samples = vec![
+            Sample {
+                size: 10,
+                part_one: Outcome::Solved(Duration::from_millis(500)),
+                part_two: Outcome::TimedOut,
+            },
+            Sample {
+                size: 20,
+                part_one: Outcome::Solved(Duration::from_secs(1)),
+                part_two: Outcome::Solved(Duration::from_secs(2)),
+            },
+        ];
+        assert_eq!(
+            "size,part_one,part_two\n10,0.5,\n20,1,2\n",
+            to_csv(&samples)
+        );
+        assert_eq!(millis(&[(20, 2000)]), solved(&samples, |s| &s.part_two));
+    }
+}
@@ -1,38 +1,75 @@
-use crate::input::AdventInput;
-use anyhow::Result;
-use gag::Gag;
+use crate::{
+    answer::Answer,
+    crosscheck::{self, CrossCheck, Verdict},
+    input::AdventInput,
+    memory::{self, AllocStats},
+    visualize::FrameSink,
+};
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use rand::{rngs::StdRng, SeedableRng};
 use std::{
     fmt::Display,
-    marker::PhantomData,
+    sync::{mpsc, Arc},
+    thread,
     time::{Duration, Instant},
 };
-pub trait Solver<T1: Display, T2: Display> {
+use tracing::{debug, error, subscriber::NoSubscriber};
+pub trait Solver {
     const DAY: u8;
     const TITLE: &'static str;
+    // Whatever both parts work from; parsed once per run and shared between them
+    type Input;
 
     fn input(&self) -> AdventInput {
         AdventInput::for_day(Self::DAY)
     }
 
-    fn solve(&self) -> Result<()> {
-        let part1 = self.part_one()?;
-        self.print_part_one(part1);
-        let part2 = self.part_two()?;
-        self.print_part_two(part2);
+    fn new() -> Self;
+
+    fn parse(&self, input: &AdventInput) -> Result<Self::Input>;
+
+    // Opt-in: catch input the parts can't cope with here, rather than panicking halfway through
+    fn validate(&self, _input: &Self::Input) -> Result<()> {
         Ok(())
     }
 
-    fn new() -> Self;
+    fn load(&self, input: &AdventInput) -> Result<Self::Input> {
+        let parsed = self.parse(input)?;
+        self.validate(&parsed)?;
+        Ok(parsed)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Result<Answer>;
+    fn part_two(&self, input: &Self::Input) -> Result<Answer>;
 
-    fn print_part_one<T: Display>(&self, result: T) {
-        println!("{}: {}", self.describe_part_one(), result);
+    // Opt-in: days that have something worth looking at can emit frames here
+    fn visualize(&self, _input: &Self::Input, _sink: &mut dyn FrameSink) -> Result<()> {
+        Err(anyhow!("{} has no visualization", self.describe()))
     }
-    fn print_part_two<T: Display>(&self, result: T) {
-        println!("{}: {}", self.describe_part_two(), result);
+
+    // Opt-in: a human-readable account of how the answers were reached, for when one of them is wrong
+    fn explain(&self, _input: &Self::Input) -> Result<String> {
+        Err(anyhow!("{} has nothing to explain", self.describe()))
+    }
+
+    // Opt-in: random but well-formed input, roughly `size` big (whatever that means for the day), for stress testing
+    fn generate(&self, _size: usize, _rng: &mut StdRng) -> Result<String> {
+        Err(anyhow!("{} has no input generator", self.describe()))
     }
 
-    fn part_one(&self) -> Result<T1>;
-    fn part_two(&self) -> Result<T2>;
+    // Opt-in: pairs of fast and slow-but-sure implementations that should always agree
+    fn crosschecks(&self) -> Vec<CrossCheck<Self::Input>> {
+        vec![]
+    }
+
+    // Straight through from text to answer, e.g. for running an example end to end
+    fn solve_part_one(&self, input: &AdventInput) -> Result<Answer> {
+        self.part_one(&self.load(input)?)
+    }
+    fn solve_part_two(&self, input: &AdventInput) -> Result<Answer> {
+        self.part_two(&self.load(input)?)
+    }
 
     fn describe(&self) -> String {
         format!("Day {:02}", Self::DAY)
@@ -48,54 +85,312 @@ pub trait Solver<T1: Display, T2: Display> {
     }
 }
 
+/// How running a single part went.
+pub enum Outcome<T> {
+    Solved(T),
+    Failed(anyhow::Error),
+    TimedOut,
+}
+impl<T> Outcome<T> {
+    pub fn solved(&self) -> Option<&T> {
+        match self {
+            Outcome::Solved(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+impl<T: Display> Display for Outcome<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Solved(v) => write!(f, "{v}"),
+            Outcome::Failed(_) => write!(f, "{}", "FAILED".red()),
+            Outcome::TimedOut => write!(f, "{}", "TIMEOUT".red()),
+        }
+    }
+}
+
+// Runs on a worker thread so a runaway part can be abandoned (and a panicking one survived).
+// There's no way to kill the thread, so a timed-out part keeps burning CPU until we exit.
+pub fn run_part<T, F>(f: F, timeout: Option<Duration>) -> Outcome<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    let received = match timeout {
+        Some(t) => rx.recv_timeout(t),
+        None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+    };
+    match received {
+        Ok(Ok(v)) => Outcome::Solved(v),
+        Ok(Err(e)) => Outcome::Failed(e),
+        Err(mpsc::RecvTimeoutError::Timeout) => Outcome::TimedOut,
+        Err(mpsc::RecvTimeoutError::Disconnected) => Outcome::Failed(anyhow!("Solver panicked")),
+    }
+}
+
+/// Each stage of a day over its real input, ready to run over and over for benchmarking.
+pub struct Stages {
+    pub parse: Box<dyn Fn() -> Result<()>>,
+    pub part_one: Box<dyn Fn() -> Result<Answer>>,
+    pub part_two: Box<dyn Fn() -> Result<Answer>>,
+}
+
 pub trait Measurable {
+    fn day(&self) -> u8;
     fn title(&self) -> String;
+    fn input(&self) -> AdventInput;
+    fn solve(
+        &self,
+        input: AdventInput,
+        timeout: Option<Duration>,
+    ) -> (Outcome<Answer>, Outcome<Answer>);
+    // Just the parse and validate, without running either part
+    fn check_input(&self, input: AdventInput) -> Result<()>;
+    fn visualize(&self, sink: &mut dyn FrameSink) -> Result<()>;
+    fn crosscheck(&self, input: AdventInput) -> Result<Vec<(&'static str, Verdict)>>;
+    fn explain(&self, input: AdventInput) -> Result<String>;
+    fn generate(&self, size: usize, seed: u64) -> Result<String>;
     fn describe_part_one(&self) -> String;
     fn describe_part_two(&self) -> String;
-    fn time_part_one(&self, iter: u8) -> Result<Duration>;
-    fn time_part_two(&self, iter: u8) -> Result<Duration>;
+    fn time_part_one(&self, iter: u8, cached: bool, timeout: Option<Duration>)
+        -> Outcome<Duration>;
+    fn time_part_two(&self, iter: u8, cached: bool, timeout: Option<Duration>)
+        -> Outcome<Duration>;
+    fn measure_part_one(&self, cached: bool, timeout: Option<Duration>) -> Outcome<AllocStats>;
+    fn measure_part_two(&self, cached: bool, timeout: Option<Duration>) -> Outcome<AllocStats>;
+    fn time_generated(
+        &self,
+        size: usize,
+        seed: u64,
+        iter: u8,
+        timeout: Option<Duration>,
+    ) -> Result<(Outcome<Duration>, Outcome<Duration>)>;
+    fn stages(&self) -> Result<Stages>;
 }
 
-pub struct Measure<T: Solver<T1, T2>, T1: Display, T2: Display> {
-    solver: T,
-    _p1: PhantomData<T1>,
-    _p2: PhantomData<T2>,
+pub struct Measure<T: Solver> {
+    solver: Arc<T>,
+}
+type Part<T> = fn(&T, &<T as Solver>::Input) -> Result<Answer>;
+impl<T> Measure<T>
+where
+    T: Solver + Send + Sync + 'static,
+    T::Input: Send + Sync + 'static,
+{
+    pub fn get(solver: T) -> Box<dyn Measurable> {
+        Box::new(Measure {
+            solver: Arc::new(solver),
+        })
+    }
+
+    fn load(solver: &T) -> Result<T::Input> {
+        solver.load(&solver.input())
+    }
+
+    // With `cached` the input is read and parsed once up front and only the part is timed,
+    // otherwise every iteration pays for the file I/O and parsing as well
+    fn time_part(
+        &self,
+        part: Part<T>,
+        iter: u8,
+        cached: bool,
+        timeout: Option<Duration>,
+    ) -> Outcome<Duration> {
+        let solver = self.solver.clone();
+        run_part(
+            move || match cached {
+                true => {
+                    let input = Self::load(&solver)?;
+                    time_execution(|| part(&solver, &input), iter)
+                }
+                false => time_execution(|| part(&solver, &Self::load(&solver)?), iter),
+            },
+            timeout,
+        )
+    }
+
+    fn measure_part(
+        &self,
+        part: Part<T>,
+        cached: bool,
+        timeout: Option<Duration>,
+    ) -> Outcome<AllocStats> {
+        let solver = self.solver.clone();
+        run_part(
+            move || match cached {
+                true => {
+                    let input = Self::load(&solver)?;
+                    measure_memory(|| part(&solver, &input))
+                }
+                false => measure_memory(|| part(&solver, &Self::load(&solver)?)),
+            },
+            timeout,
+        )
+    }
 }
-impl<T: Solver<T1, T2>, T1: Display, T2: Display> Measurable for Measure<T, T1, T2> {
+impl<T> Measurable for Measure<T>
+where
+    T: Solver + Send + Sync + 'static,
+    T::Input: Send + Sync + 'static,
+{
+    fn day(&self) -> u8 {
+        T::DAY
+    }
     fn title(&self) -> String {
         String::from(T::TITLE)
     }
+    fn input(&self) -> AdventInput {
+        self.solver.input()
+    }
+    fn solve(
+        &self,
+        input: AdventInput,
+        timeout: Option<Duration>,
+    ) -> (Outcome<Answer>, Outcome<Answer>) {
+        debug!("Solving {}: {}", self.solver.describe(), T::TITLE);
+        let solver = self.solver.clone();
+        let input = match run_part(move || solver.load(&input).map(Arc::new), timeout) {
+            Outcome::Solved(input) => input,
+            // Neither part can run without its input, so they both go down with it
+            Outcome::Failed(e) => {
+                let fail = || Outcome::Failed(anyhow!("Bad input: {e:#}"));
+                let (part1, part2) = (fail(), fail());
+                report_failure(self.describe_part_one(), &part1);
+                report_failure(self.describe_part_two(), &part2);
+                return (part1, part2);
+            }
+            Outcome::TimedOut => return (Outcome::TimedOut, Outcome::TimedOut),
+        };
+        let (solver, shared) = (self.solver.clone(), input.clone());
+        let part1 = run_part(move || solver.part_one(&shared), timeout);
+        report_failure(self.describe_part_one(), &part1);
+        let solver = self.solver.clone();
+        let part2 = run_part(move || solver.part_two(&input), timeout);
+        report_failure(self.describe_part_two(), &part2);
+        (part1, part2)
+    }
+    fn check_input(&self, input: AdventInput) -> Result<()> {
+        self.solver.load(&input).map(|_| ())
+    }
+    fn visualize(&self, sink: &mut dyn FrameSink) -> Result<()> {
+        let input = Self::load(&self.solver)?;
+        self.solver.visualize(&input, sink)
+    }
+    fn crosscheck(&self, input: AdventInput) -> Result<Vec<(&'static str, Verdict)>> {
+        crosscheck::run(self.solver.as_ref(), &input.get()?)
+    }
+    fn explain(&self, input: AdventInput) -> Result<String> {
+        self.solver.explain(&self.solver.load(&input)?)
+    }
+    fn generate(&self, size: usize, seed: u64) -> Result<String> {
+        self.solver.generate(size, &mut StdRng::seed_from_u64(seed))
+    }
     fn describe_part_one(&self) -> String {
         self.solver.describe_part_one()
     }
     fn describe_part_two(&self) -> String {
         self.solver.describe_part_two()
     }
-    fn time_part_one(&self, iter: u8) -> Result<Duration> {
-        time_execution(|| self.solver.part_one(), iter)
+    fn time_part_one(
+        &self,
+        iter: u8,
+        cached: bool,
+        timeout: Option<Duration>,
+    ) -> Outcome<Duration> {
+        let outcome = self.time_part(T::part_one, iter, cached, timeout);
+        report_failure(self.describe_part_one(), &outcome);
+        outcome
     }
-    fn time_part_two(&self, iter: u8) -> Result<Duration> {
-        time_execution(|| self.solver.part_two(), iter)
+    fn time_part_two(
+        &self,
+        iter: u8,
+        cached: bool,
+        timeout: Option<Duration>,
+    ) -> Outcome<Duration> {
+        let outcome = self.time_part(T::part_two, iter, cached, timeout);
+        report_failure(self.describe_part_two(), &outcome);
+        outcome
     }
-}
-impl<T: 'static + Solver<T1, T2>, T1: 'static + Display, T2: 'static + Display> Measure<T, T1, T2> {
-    pub fn get(solver: T) -> Box<dyn Measurable> {
-        Box::new(Measure {
-            solver,
-            _p1: PhantomData {},
-            _p2: PhantomData {},
+    fn measure_part_one(&self, cached: bool, timeout: Option<Duration>) -> Outcome<AllocStats> {
+        let outcome = self.measure_part(T::part_one, cached, timeout);
+        report_failure(self.describe_part_one(), &outcome);
+        outcome
+    }
+    fn measure_part_two(&self, cached: bool, timeout: Option<Duration>) -> Outcome<AllocStats> {
+        let outcome = self.measure_part(T::part_two, cached, timeout);
+        report_failure(self.describe_part_two(), &outcome);
+        outcome
+    }
+    // Only the parts are timed; the input is generated and parsed once up front
+    fn time_generated(
+        &self,
+        size: usize,
+        seed: u64,
+        iter: u8,
+        timeout: Option<Duration>,
+    ) -> Result<(Outcome<Duration>, Outcome<Duration>)> {
+        let text = self.generate(size, seed)?;
+        let input = Arc::new(self.solver.load(&AdventInput::from_text(text))?);
+        let time = |part: Part<T>| {
+            let (solver, input) = (self.solver.clone(), input.clone());
+            run_part(
+                move || time_execution(|| part(&solver, &input), iter),
+                timeout,
+            )
+        };
+        let (part1, part2) = (time(T::part_one), time(T::part_two));
+        report_failure(self.describe_part_one(), &part1);
+        report_failure(self.describe_part_two(), &part2);
+        Ok((part1, part2))
+    }
+    // The file is only read once, so parsing is timed on its own without the I/O
+    fn stages(&self) -> Result<Stages> {
+        let text = self.input().get()?;
+        let input = Arc::new(self.solver.load(&AdventInput::from_text(text.as_str()))?);
+        let solver = self.solver.clone();
+        let parse = move || {
+            solver
+                .load(&AdventInput::from_text(text.as_str()))
+                .map(|_| ())
+        };
+        let part = |part: Part<T>| {
+            let (solver, input) = (self.solver.clone(), input.clone());
+            Box::new(move || part(&solver, &input))
+        };
+        Ok(Stages {
+            parse: Box::new(parse),
+            part_one: part(T::part_one),
+            part_two: part(T::part_two),
         })
     }
 }
 
+fn report_failure<T>(description: String, outcome: &Outcome<T>) {
+    if let Outcome::Failed(e) = outcome {
+        error!("{description} failed: {e:#}");
+    }
+}
+
 fn time_execution<F: Fn() -> Result<T>, T>(f: F, iter: u8) -> Result<Duration> {
-    let gag = Gag::stdout()?;
-    let mut tot = Duration::ZERO;
-    for _ in 0..iter {
-        let now = Instant::now();
-        f()?;
-        tot += now.elapsed();
-    }
-    drop(gag);
-    Ok(tot / iter.into())
+    // Logging would skew the timings, so swap in a subscriber that drops everything
+    tracing::subscriber::with_default(NoSubscriber::default(), || {
+        let mut tot = Duration::ZERO;
+        for _ in 0..iter {
+            let now = Instant::now();
+            f()?;
+            tot += now.elapsed();
+        }
+        Ok(tot / iter.into())
+    })
+}
+
+fn measure_memory<F: FnOnce() -> Result<T>, T>(f: F) -> Result<AllocStats> {
+    tracing::subscriber::with_default(NoSubscriber::default(), || {
+        let (res, stats) = memory::measure(f);
+        res.map(|_| stats)
+    })
 }
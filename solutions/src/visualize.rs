@@ -0,0 +1,170 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::{fmt::Write as _, fs, io::Write, path::PathBuf, thread, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+impl Rgb {
+    // Cheap, stable way to give every id its own colour
+    pub fn from_id(id: usize) -> Self {
+        let h = (id as u32).wrapping_mul(2_654_435_761);
+        Rgb(
+            64 + (h >> 24) as u8 % 192,
+            64 + (h >> 16) as u8 % 192,
+            64 + (h >> 8) as u8 % 192,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    glyph: char,
+    color: Option<Rgb>,
+}
+
+/// A single snapshot of a solver's state, as a grid of (optionally coloured) characters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![
+                Cell {
+                    glyph: ' ',
+                    color: None
+                };
+                width * height
+            ],
+        }
+    }
+
+    pub fn from_text(text: &str) -> Self {
+        let lines: Vec<_> = text.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut frame = Frame::new(width, lines.len());
+        for (y, l) in lines.iter().enumerate() {
+            for (x, c) in l.chars().enumerate() {
+                frame.set(x, y, c, None);
+            }
+        }
+        frame
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, glyph: char, color: Option<Rgb>) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = Cell { glyph, color };
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            out.extend(row.iter().map(|c| c.glyph));
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn to_colored(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            for c in row {
+                let _ = match c.color {
+                    Some(Rgb(r, g, b)) => write!(out, "{}", c.glyph.to_string().truecolor(r, g, b)),
+                    None => write!(out, "{}", c.glyph),
+                };
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Binary PPM, one pixel per cell.  Uncoloured cells are black if empty and grey otherwise.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for c in &self.cells {
+            let Rgb(r, g, b) = match (c.color, c.glyph) {
+                (Some(color), _) => color,
+                (None, ' ' | '.') => Rgb(0, 0, 0),
+                (None, _) => Rgb(160, 160, 160),
+            };
+            out.extend([r, g, b]);
+        }
+        out
+    }
+}
+
+/// Anything that can receive frames from a solver's visualization.
+pub trait FrameSink {
+    fn emit(&mut self, frame: &Frame) -> Result<()>;
+}
+
+pub struct TerminalSink {
+    delay: Duration,
+}
+impl TerminalSink {
+    pub fn new(delay: Duration) -> Self {
+        TerminalSink { delay }
+    }
+}
+impl FrameSink for TerminalSink {
+    fn emit(&mut self, frame: &Frame) -> Result<()> {
+        let mut stdout = std::io::stdout().lock();
+        // Clear the screen and move the cursor home before drawing
+        write!(stdout, "\x1B[2J\x1B[H{}", frame.to_colored())?;
+        stdout.flush()?;
+        thread::sleep(self.delay);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameFormat {
+    Text,
+    Ppm,
+}
+
+pub struct DirectorySink {
+    dir: PathBuf,
+    format: FrameFormat,
+    count: usize,
+}
+impl DirectorySink {
+    pub fn new(dir: PathBuf, format: FrameFormat) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(DirectorySink {
+            dir,
+            format,
+            count: 0,
+        })
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+impl FrameSink for DirectorySink {
+    fn emit(&mut self, frame: &Frame) -> Result<()> {
+        let (ext, data) = match self.format {
+            FrameFormat::Text => ("txt", frame.to_text().into_bytes()),
+            FrameFormat::Ppm => ("ppm", frame.to_ppm()),
+        };
+        fs::write(self.dir.join(format!("frame{:05}.{ext}", self.count)), data)?;
+        self.count += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc,
+    time::Duration,
+};
+use tracing::{debug, info};
+
+// Editors love to save by writing a temp file and renaming it over the original,
+// so watch the directories and pick out our files rather than watching the files themselves.
+const SOURCE_DIR: &str = "solutions/src/days";
+const INPUT_DIR: &str = "inputs";
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub fn watch(day: u8) -> Result<()> {
+    let source = PathBuf::from(format!("{SOURCE_DIR}/day{day:02}.rs"));
+    let input = PathBuf::from(format!("{INPUT_DIR}/day{day:02}.txt"));
+    if !source.exists() {
+        return Err(anyhow!(
+            "Can't find {}; watch needs to run from the repository root",
+            source.display()
+        ));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(Path::new(SOURCE_DIR), RecursiveMode::NonRecursive)?;
+    watcher.watch(Path::new(INPUT_DIR), RecursiveMode::NonRecursive)?;
+
+    let watched = [source.file_name(), input.file_name()];
+    let is_relevant = |event: &Event| {
+        matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) && event.paths.iter().any(|p| watched.contains(&p.file_name()))
+    };
+
+    run_day(day)?;
+    loop {
+        let event = rx.recv()??;
+        if !is_relevant(&event) {
+            continue;
+        }
+        debug!("Change detected: {:?}", event.paths);
+        // A single save tends to fire a handful of events, so wait for things to settle
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+        run_day(day)?;
+    }
+}
+
+fn run_day(day: u8) -> Result<()> {
+    // Clear the screen so only the latest run is visible
+    print!("\x1B[2J\x1B[H");
+    println!("{}", format!("Day {day:02}").bold());
+    println!("{}", "Examples".underline());
+
+    // The examples (and their expected answers) live in each day's unit tests
+    let filter = format!("days::day{day:02}::");
+    info!("Running cargo test for {filter}");
+    let output = Command::new("cargo")
+        .args(["test", "--lib", "--", &filter])
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let results = parse_test_results(&stdout);
+    if results.is_empty() {
+        // Most likely a compile error, in which case cargo's own output is the most useful thing
+        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+        println!("{}", "Build failed".red().bold());
+        return Ok(());
+    }
+    for (name, passed) in &results {
+        if *passed {
+            println!("  {} {name}", "PASS".green());
+        } else {
+            println!("  {} {name}", "FAIL".red().bold());
+        }
+    }
+
+    println!("\n{}", "Real input".underline());
+    Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--bin",
+            "solutions",
+            "--",
+            "--day",
+            &day.to_string(),
+        ])
+        .status()?;
+    println!("\n{}", "Watching for changes...".dimmed());
+    Ok(())
+}
+
+// Pulls "test days::day05::tests::should_parse ... ok" lines out of libtest's output
+fn parse_test_results(output: &str) -> Vec<(String, bool)> {
+    output
+        .lines()
+        .filter_map(|l| {
+            let rest = l.strip_prefix("test ")?;
+            let (name, status) = rest.split_once(" ... ")?;
+            let name = name.rsplit("::").next().unwrap_or(name);
+            Some((name.to_owned(), status.trim() == "ok"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_test_results() {
+        let output = "running 2 tests
+test days::day05::tests::should_parse ... ok
+test days::day05::tests::should_solve_part1 ... FAILED
+
+failures:";
+        assert_eq!(
+            vec![
+                ("should_parse".to_owned(), true),
+                ("should_solve_part1".to_owned(), false)
+            ],
+            parse_test_results(output)
+        );
+    }
+}